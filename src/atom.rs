@@ -6,6 +6,8 @@ use std::{mem, ptr};
 use pkgcraft::{atom, eapi, restrict, utils::hash};
 
 use crate::macros::*;
+use crate::parse::eapi_or_default;
+use crate::restrict::Restrict;
 
 pub mod version;
 
@@ -26,10 +28,30 @@ pub struct Atom;
 pub unsafe extern "C" fn pkgcraft_atom(
     atom: *const c_char,
     eapi: *const c_char,
+) -> *mut atom::Atom {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
+    unsafe { pkgcraft_atom_with_eapi(atom, eapi) }
+}
+
+/// Parse a string into an atom using a specific EAPI. Pass NULL for the eapi argument in
+/// order to parse using the latest EAPI with extensions (e.g. support for repo deps).
+///
+/// This avoids resolving the EAPI on every call when parsing many atoms using an Eapi pointer
+/// received from pkgcraft_get_eapi() or similar.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The atom argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_atom_with_eapi(
+    atom: *const c_char,
+    eapi: *const eapi::Eapi,
 ) -> *mut atom::Atom {
     let atom = null_ptr_check!(atom.as_ref());
     let atom = unsafe { unwrap_or_return!(CStr::from_ptr(atom).to_str(), ptr::null_mut()) };
-    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, ptr::null_mut());
     let atom = unwrap_or_return!(atom::Atom::new(atom, eapi), ptr::null_mut());
     Box::into_raw(Box::new(atom))
 }
@@ -267,9 +289,10 @@ pub unsafe extern "C" fn pkgcraft_atom_hash(atom: *mut atom::Atom) -> u64 {
 /// # Safety
 /// The argument must be a non-null Atom pointer.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_atom_restrict(atom: *mut atom::Atom) -> *mut restrict::Restrict {
+pub unsafe extern "C" fn pkgcraft_atom_restrict(atom: *mut atom::Atom) -> *mut Restrict {
     let atom = null_ptr_check!(atom.as_ref());
-    Box::into_raw(Box::new(atom.into()))
+    let restrict: restrict::Restrict = atom.into();
+    Box::into_raw(Box::new(restrict.into()))
 }
 
 /// Free an atom.
@@ -282,3 +305,18 @@ pub unsafe extern "C" fn pkgcraft_atom_free(atom: *mut atom::Atom) {
         unsafe { drop(Box::from_raw(atom)) };
     }
 }
+
+/// Free an array of atoms.
+///
+/// # Safety
+/// The argument must be an array of Atom pointers or NULL along with the length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_atoms_free(atoms: *mut *mut atom::Atom, len: usize) {
+    if !atoms.is_null() {
+        unsafe {
+            for a in Vec::from_raw_parts(atoms, len, len).into_iter() {
+                pkgcraft_atom_free(a);
+            }
+        }
+    }
+}
@@ -1,35 +1,653 @@
-use std::ffi::{CStr, CString};
+use std::collections::HashSet;
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::{c_char, c_int};
-use std::{mem, ptr};
+use std::path::{Path, PathBuf};
+use std::{env, fs, mem, ptr, slice};
 
+use pkgcraft::pkg::Package;
 use pkgcraft::repo::Repository;
-use pkgcraft::{config, repo};
+use pkgcraft::restrict::Restriction;
+use pkgcraft::{config, pkg, repo};
 
 use crate::macros::*;
-use crate::repo::RepoFormat;
+use crate::repo::{RepoFormat, RestrictPkgIter};
+use crate::restrict::Restrict;
+use crate::{Error, Result};
+
+mod ini;
+mod portage;
+mod sync;
+
+/// Options used to create a config.
+///
+/// All path fields may be NULL to use their defaults. With a prefix, the default config, cache,
+/// and data directories are {prefix}/etc/pkgcraft, {prefix}/var/cache/pkgcraft, and
+/// {prefix}/var/lib/pkgcraft, respectively. Otherwise the related XDG user directories are used.
+/// All directories are passed through to pkgcraft.
+///
+/// The config directory holds the repos.conf file used by pkgcraft_config_save_repos_conf() while
+/// the data directory holds repos synced via pkgcraft_config_sync() that lack a location.
+#[repr(C)]
+pub struct ConfigOptions {
+    config_dir: *const c_char,
+    cache_dir: *const c_char,
+    data_dir: *const c_char,
+    prefix: *const c_char,
+    system: bool,
+}
+
+/// Convert a nullable string into an optional owned string.
+///
+/// # Safety
+/// The argument must be a valid string pointer or NULL.
+unsafe fn optional_str(s: *const c_char) -> Result<Option<String>> {
+    match unsafe { s.as_ref() } {
+        None => Ok(None),
+        Some(s) => {
+            let s = unsafe { CStr::from_ptr(s).to_str() }
+                .map_err(|e| Error::new(format!("invalid string: {e}")))?;
+            Ok(Some(s.to_string()))
+        }
+    }
+}
+
+/// Convert a nullable string into an optional path.
+///
+/// # Safety
+/// The argument must be a valid string pointer or NULL.
+unsafe fn path(s: *const c_char) -> Result<Option<PathBuf>> {
+    Ok(unsafe { optional_str(s) }?.map(PathBuf::from))
+}
+
+/// Convert a path into an owned C string.
+fn path_str(path: &Path) -> *mut c_char {
+    CString::new(path.to_string_lossy().as_ref())
+        .unwrap()
+        .into_raw()
+}
+
+/// Return a pkgcraft-specific XDG user directory.
+fn user_dir(var: &str, fallback: &str) -> Result<PathBuf> {
+    match (env::var_os(var), env::var_os("HOME")) {
+        (Some(dir), _) if !dir.is_empty() => Ok(PathBuf::from(dir).join("pkgcraft")),
+        (_, Some(home)) => Ok(PathBuf::from(home).join(fallback).join("pkgcraft")),
+        _ => Err(Error::new(format!(
+            "failed determining user dir: {var} and HOME unset"
+        ))),
+    }
+}
+
+/// Repo registered with a config.
+struct ConfigRepo {
+    id: String,
+    priority: i32,
+    location: PathBuf,
+    sync_type: Option<String>,
+    sync_uri: Option<String>,
+    masters: Option<Vec<String>>,
+    repo: repo::Repo,
+}
+
+impl ConfigRepo {
+    fn new<P: AsRef<Path>>(repo: repo::Repo, location: P) -> Self {
+        Self {
+            id: repo.id().to_string(),
+            priority: repo.priority(),
+            location: location.as_ref().to_path_buf(),
+            sync_type: None,
+            sync_uri: None,
+            masters: None,
+            repo,
+        }
+    }
+
+    /// Update the repo settings from one of its repos.conf sections, overriding the settings it
+    /// defines.
+    fn load_section(&mut self, section: &ini::Section) {
+        if let Some(location) = section.get("location") {
+            self.location = PathBuf::from(location);
+        }
+        if let Some(Ok(priority)) = section.get("priority").map(|s| s.parse()) {
+            self.priority = priority;
+        }
+        if let Some(sync_type) = section.get("sync-type") {
+            self.sync_type = Some(sync_type.to_string());
+        }
+        if let Some(sync_uri) = section.get("sync-uri") {
+            self.sync_uri = Some(sync_uri.to_string());
+        }
+        if let Some(masters) = section.get("masters") {
+            self.masters = Some(masters.split_whitespace().map(|s| s.to_string()).collect());
+        }
+    }
+
+    /// Return the optional repos.conf settings for the repo.
+    fn optional_settings(&self) -> [(&'static str, Option<String>); 3] {
+        [
+            ("sync-type", self.sync_type.clone()),
+            ("sync-uri", self.sync_uri.clone()),
+            ("masters", self.masters.as_ref().map(|m| m.join(" "))),
+        ]
+    }
+
+    /// Update a repos.conf section from the repo settings, leaving unrelated entries untouched.
+    fn update_section(&self, section: &mut ini::Section) {
+        section.set("location", &self.location.to_string_lossy());
+        section.set("priority", &self.priority.to_string());
+        for (key, val) in self.optional_settings() {
+            if let Some(val) = val {
+                section.set(key, &val);
+            }
+        }
+    }
+
+    /// Remove the optional settings unset for the repo from a repos.conf section.
+    fn clear_section(&self, section: &mut ini::Section) {
+        for (key, val) in self.optional_settings() {
+            if val.is_none() {
+                section.remove(key);
+            }
+        }
+    }
+}
+
+/// Return the files for a given repos.conf file or directory.
+///
+/// Hidden files in directories are ignored and the remaining files are returned in sorted order.
+fn repos_conf_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let entries =
+        fs::read_dir(path).map_err(|e| Error::new(format!("failed reading {path:?}: {e}")))?;
+    let mut files = vec![];
+    for entry in entries {
+        let entry = entry.map_err(|e| Error::new(format!("failed reading {path:?}: {e}")))?;
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
 
 // explicitly force symbols to be exported
 // TODO: https://github.com/rust-lang/rfcs/issues/2771
 /// Opaque wrapper for Config objects.
-pub struct Config;
+pub struct Config {
+    config: config::Config,
+    repos: Vec<ConfigRepo>,
+    portage: portage::PortageConfig,
+    config_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    prefix: PathBuf,
+}
+
+impl Config {
+    /// Create a config using the given options, loading the system config if requested.
+    ///
+    /// # Safety
+    /// The options argument must be a ConfigOptions pointer or NULL.
+    unsafe fn new(options: *const ConfigOptions) -> Result<Self> {
+        let options = unsafe { options.as_ref() };
+        let (config_dir, cache_dir, data_dir, prefix, system) = match options {
+            None => (None, None, None, None, false),
+            Some(o) => unsafe {
+                (
+                    path(o.config_dir)?,
+                    path(o.cache_dir)?,
+                    path(o.data_dir)?,
+                    path(o.prefix)?,
+                    o.system,
+                )
+            },
+        };
+
+        let prefix = prefix.unwrap_or_default();
+        let prefix_str = prefix
+            .to_str()
+            .ok_or_else(|| Error::new(format!("invalid prefix: {prefix:?}")))?;
+        let mut config = Self {
+            config: config::Config::new("pkgcraft", prefix_str, false)?,
+            repos: vec![],
+            portage: Default::default(),
+            config_dir,
+            cache_dir,
+            data_dir,
+            prefix,
+        };
+        config.set_paths();
+
+        if system {
+            config.load_system()?;
+        }
+
+        Ok(config)
+    }
+
+    /// Return a directory, falling back to its default under the prefix or the XDG user directory.
+    fn dir(
+        &self,
+        dir: Option<&PathBuf>,
+        var: &str,
+        fallback: &str,
+        subdir: &str,
+    ) -> Result<PathBuf> {
+        match (dir, self.prefix.as_os_str().is_empty()) {
+            (Some(dir), _) => Ok(dir.clone()),
+            (None, true) => user_dir(var, fallback),
+            (None, false) => Ok(self.prefix.join(subdir).join("pkgcraft")),
+        }
+    }
+
+    /// Return the config directory.
+    fn config_dir(&self) -> Result<PathBuf> {
+        self.dir(
+            self.config_dir.as_ref(),
+            "XDG_CONFIG_HOME",
+            ".config",
+            "etc",
+        )
+    }
+
+    /// Return the cache directory.
+    fn cache_dir(&self) -> Result<PathBuf> {
+        self.dir(
+            self.cache_dir.as_ref(),
+            "XDG_CACHE_HOME",
+            ".cache",
+            "var/cache",
+        )
+    }
+
+    /// Return the data directory.
+    fn data_dir(&self) -> Result<PathBuf> {
+        self.dir(
+            self.data_dir.as_ref(),
+            "XDG_DATA_HOME",
+            ".local/share",
+            "var/lib",
+        )
+    }
+
+    /// Pass the config's directories through to pkgcraft.
+    ///
+    /// Directories that can't be determined, e.g. user directories without HOME set, are left
+    /// to pkgcraft's defaults while the related accessors return errors.
+    fn set_paths(&mut self) {
+        if let Ok(dir) = self.config_dir() {
+            self.config.path.config = dir;
+        }
+        if let Ok(dir) = self.cache_dir() {
+            self.config.path.cache = dir;
+        }
+        if let Ok(dir) = self.data_dir() {
+            self.config.path.data = dir;
+        }
+    }
+
+    /// Return the path to the system portage config directory.
+    fn portage_dir(&self) -> PathBuf {
+        self.prefix.join("etc/portage")
+    }
+
+    /// Load the system portage config.
+    fn load_system(&mut self) -> Result<()> {
+        let dir = self.portage_dir();
+        let path = dir.join("repos.conf");
+        if path.exists() {
+            self.load_repos_conf(&path)?;
+        }
+        self.portage.load(&dir)
+    }
+
+    /// Load and register the repos from a given repos.conf file or directory.
+    ///
+    /// Matching portage, settings from later sections for a repo override earlier ones.
+    fn load_repos_conf(&mut self, path: &Path) -> Result<Vec<repo::Repo>> {
+        let repos = self.config.load_repos_conf(path)?;
+        let mut sections = vec![];
+        for file in repos_conf_files(path)? {
+            sections.extend(ini::Ini::load(&file)?.sections().iter().cloned());
+        }
+
+        for repo in &repos {
+            let mut r = ConfigRepo::new(repo.clone(), "");
+            let matching: Vec<_> = sections.iter().filter(|s| s.name() == repo.id()).collect();
+            for section in &matching {
+                r.load_section(section);
+            }
+            // re-register repos whose merged settings may differ from those pkgcraft used
+            let reload = !r.location.as_os_str().is_empty()
+                && (matching.len() > 1 || r.priority != repo.priority());
+            self.add_repo(r);
+            if reload {
+                self.register_repo(repo.id())?;
+            }
+        }
+
+        Ok(repos)
+    }
+
+    /// Save the registered repos to a given repos.conf file or directory.
+    ///
+    /// Each repo's settings are written to its last existing section, since later sections
+    /// override earlier ones, with unset settings removed from all its sections. All other
+    /// sections, entries, and comments are kept. New repos are written to separate {id}.conf
+    /// files for directories. Repos without a location are skipped.
+    fn save_repos_conf(&self, path: &Path) -> Result<()> {
+        let files = match (path.is_dir(), path.exists()) {
+            (true, _) => repos_conf_files(path)?,
+            (false, true) => vec![path.to_path_buf()],
+            (false, false) => vec![],
+        };
+        let mut inis = vec![];
+        for file in files {
+            let ini = ini::Ini::load(&file)?;
+            inis.push((file, Some(ini.clone()), ini));
+        }
+
+        for r in self
+            .repos
+            .iter()
+            .filter(|r| !r.location.as_os_str().is_empty())
+        {
+            for (_, _, ini) in &mut inis {
+                for section in ini.sections_mut().iter_mut().filter(|s| s.name() == r.id) {
+                    r.clear_section(section);
+                }
+            }
+
+            let idx = match inis
+                .iter()
+                .rposition(|(_, _, ini)| ini.section(&r.id).is_some())
+            {
+                Some(idx) => idx,
+                None => {
+                    let file = match path.is_dir() {
+                        true => path.join(format!("{}.conf", r.id)),
+                        false => path.to_path_buf(),
+                    };
+                    match inis.iter().position(|(f, _, _)| f == &file) {
+                        Some(idx) => idx,
+                        None => {
+                            inis.push((file, None, ini::Ini::default()));
+                            inis.len() - 1
+                        }
+                    }
+                }
+            };
+            r.update_section(inis[idx].2.section_mut(&r.id));
+        }
+
+        for (file, orig, ini) in inis {
+            if orig.as_ref() != Some(&ini) {
+                ini.save(&file)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Register a repo, replacing any existing repo with the same id.
+    fn add_repo(&mut self, repo: ConfigRepo) {
+        self.repos.retain(|r| r.id != repo.id);
+        self.repos.push(repo);
+        self.sort_repos();
+    }
+
+    /// Sort registered repos by descending priority, falling back to their ids.
+    fn sort_repos(&mut self) {
+        self.repos
+            .sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    /// Sync a registered repo from its local sync URI, re-registering it afterwards.
+    ///
+    /// Repos without a location are synced to {data_dir}/repos/{id}.
+    fn sync_repo<F: FnMut(&str)>(&mut self, id: &str, progress: F) -> Result<()> {
+        let idx = self.repo_index(id)?;
+        let r = &self.repos[idx];
+        let uri = r
+            .sync_uri
+            .as_deref()
+            .ok_or_else(|| Error::new(format!("repo missing sync uri: {id}")))?;
+        let uri = Path::new(uri.strip_prefix("file://").unwrap_or(uri));
+        let location = match r.location.as_os_str().is_empty() {
+            true => self.data_dir()?.join("repos").join(id),
+            false => r.location.clone(),
+        };
+
+        let syncer = sync::Syncer::new(r.sync_type.as_deref(), uri)?;
+        syncer.sync(uri, &location, progress)?;
+
+        // reload the existing repo entry so it reflects the synced content
+        self.repos[idx].location = location;
+        self.register_repo(id)
+    }
+
+    /// Remove a registered repo.
+    fn remove_repo(&mut self, id: &str) -> Result<()> {
+        let idx = self.repo_index(id)?;
+        self.config.del_repos(&[id], false)?;
+        self.repos.remove(idx);
+        Ok(())
+    }
+
+    /// Re-register a repo with pkgcraft using its current location and priority.
+    fn register_repo(&mut self, id: &str) -> Result<()> {
+        let idx = self.repo_index(id)?;
+        let r = &self.repos[idx];
+        if r.location.as_os_str().is_empty() {
+            return Err(Error::new(format!("repo missing location: {id}")));
+        }
+        let path = r
+            .location
+            .to_str()
+            .ok_or_else(|| Error::new(format!("invalid repo location: {:?}", r.location)))?;
+        self.config.del_repos(&[id], false)?;
+        let repo = self.config.add_repo_path(id, r.priority, path)?;
+        self.repos[idx].repo = repo;
+        self.sort_repos();
+        Ok(())
+    }
+
+    /// Change the priority of a registered repo, re-registering it so its repo object agrees.
+    fn set_repo_priority(&mut self, id: &str, priority: i32) -> Result<()> {
+        let idx = self.repo_index(id)?;
+        let old = mem::replace(&mut self.repos[idx].priority, priority);
+        let result = self.register_repo(id);
+        if result.is_err() {
+            self.repos[idx].priority = old;
+        }
+        result
+    }
+
+    /// Return the position of a registered repo.
+    fn repo_index(&self, id: &str) -> Result<usize> {
+        self.repos
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or_else(|| Error::new(format!("nonexistent repo: {id}")))
+    }
+}
+
+/// Opaque wrapper for ConfigPkgIter objects.
+///
+/// Packages are returned from all of a config's repos in priority order.
+pub struct ConfigPkgIter<'a> {
+    repos: std::slice::Iter<'a, ConfigRepo>,
+    restrict: Option<Restrict>,
+    // cpvs of previously seen packages when skipping shadowed packages
+    seen: Option<HashSet<String>>,
+    iter: Option<Box<dyn Iterator<Item = pkg::Pkg<'a>> + 'a>>,
+}
+
+impl<'a> ConfigPkgIter<'a> {
+    fn new(config: &'a Config, restrict: Option<Restrict>, skip_shadowed: bool) -> Self {
+        Self {
+            repos: config.repos.iter(),
+            restrict,
+            seen: skip_shadowed.then(HashSet::new),
+            iter: None,
+        }
+    }
+
+    /// Return the package iterator for the next repo.
+    fn next_repo(&mut self) -> Option<Box<dyn Iterator<Item = pkg::Pkg<'a>> + 'a>> {
+        let repo = &self.repos.next()?.repo;
+        // shadowing requires all packages to be seen regardless of restrictions
+        match (&self.restrict, &self.seen) {
+            (Some(r), None) => Some(Box::new(RestrictPkgIter::new(repo, r))),
+            _ => Some(Box::new(repo.iter())),
+        }
+    }
+}
+
+impl<'a> Iterator for ConfigPkgIter<'a> {
+    type Item = pkg::Pkg<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(iter) = self.iter.as_mut() {
+                for pkg in iter.by_ref() {
+                    if let Some(seen) = self.seen.as_mut() {
+                        if !seen.insert(pkg.atom().cpv()) {
+                            continue;
+                        }
+                        if let Some(r) = &self.restrict {
+                            if !r.matches(&pkg) {
+                                continue;
+                            }
+                        }
+                    }
+                    return Some(pkg);
+                }
+            }
+            self.iter = Some(self.next_repo()?);
+        }
+    }
+}
 
 /// Wrapper for configured repos.
+///
+/// The repo pointer is an owned clone of the configured repo that is freed along with the
+/// remaining fields via pkgcraft_repo_config_free(). The sync_type, sync_uri, and masters fields
+/// are NULL when unset.
 #[repr(C)]
 pub struct RepoConfig {
     id: *mut c_char,
     format: RepoFormat,
-    repo: *const repo::Repo,
+    repo: *mut repo::Repo,
+    location: *mut c_char,
+    priority: c_int,
+    sync_type: *mut c_char,
+    sync_uri: *mut c_char,
+    masters: *mut *mut c_char,
+    masters_len: usize,
+}
+
+impl RepoConfig {
+    /// Create a repo config for a registered repo, cloning its repo.
+    fn new(r: &ConfigRepo) -> Self {
+        let optional_str = |s: Option<&String>| match s {
+            Some(s) => CString::new(s.as_str()).unwrap().into_raw(),
+            None => ptr::null_mut(),
+        };
+        let (masters, masters_len) = match &r.masters {
+            Some(masters) => {
+                let mut ptrs: Vec<_> = masters
+                    .iter()
+                    .map(|s| CString::new(s.as_str()).unwrap().into_raw())
+                    .collect();
+                ptrs.shrink_to_fit();
+                let len = ptrs.len();
+                let p = ptrs.as_mut_ptr();
+                mem::forget(ptrs);
+                (p, len)
+            }
+            None => (ptr::null_mut(), 0),
+        };
+
+        RepoConfig {
+            id: CString::new(r.id.as_str()).unwrap().into_raw(),
+            format: (&r.repo).into(),
+            repo: Box::into_raw(Box::new(r.repo.clone())),
+            location: path_str(&r.location),
+            priority: r.priority,
+            sync_type: optional_str(r.sync_type.as_ref()),
+            sync_uri: optional_str(r.sync_uri.as_ref()),
+            masters,
+            masters_len,
+        }
+    }
 }
 
 /// Return the pkgcraft config for the system.
 ///
 /// Returns NULL on error.
 #[no_mangle]
-pub extern "C" fn pkgcraft_config() -> *mut config::Config {
-    let config = unwrap_or_return!(config::Config::new("pkgcraft", "", false), ptr::null_mut());
+pub extern "C" fn pkgcraft_config() -> *mut Config {
+    let config = unwrap_or_return!(unsafe { Config::new(ptr::null()) }, ptr::null_mut());
     Box::into_raw(Box::new(config))
 }
 
+/// Create a config using the given options. Pass NULL for the options argument in order to use
+/// the defaults, matching pkgcraft_config().
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a ConfigOptions pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_new(options: *const ConfigOptions) -> *mut Config {
+    let config = unwrap_or_return!(unsafe { Config::new(options) }, ptr::null_mut());
+    Box::into_raw(Box::new(config))
+}
+
+/// Return the config directory for a config.
+///
+/// Returns NULL on error, e.g. when the default user directory can't be determined.
+///
+/// # Safety
+/// The argument must be a non-null Config pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_dir(config: *mut Config) -> *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let dir = unwrap_or_return!(config.config_dir(), ptr::null_mut());
+    path_str(&dir)
+}
+
+/// Return the cache directory for a config.
+///
+/// Returns NULL on error, e.g. when the default user directory can't be determined.
+///
+/// # Safety
+/// The argument must be a non-null Config pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_cache_dir(config: *mut Config) -> *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let dir = unwrap_or_return!(config.cache_dir(), ptr::null_mut());
+    path_str(&dir)
+}
+
+/// Return the data directory for a config.
+///
+/// Returns NULL on error, e.g. when the default user directory can't be determined.
+///
+/// # Safety
+/// The argument must be a non-null Config pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_data_dir(config: *mut Config) -> *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let dir = unwrap_or_return!(config.data_dir(), ptr::null_mut());
+    path_str(&dir)
+}
+
 /// Add local repo from filesystem path.
 ///
 /// Returns NULL on error.
@@ -38,7 +656,7 @@ pub extern "C" fn pkgcraft_config() -> *mut config::Config {
 /// The path argument should be a valid path on the system.
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_config_add_repo_path(
-    config: *mut config::Config,
+    config: *mut Config,
     id: *const c_char,
     priority: c_int,
     path: *const c_char,
@@ -50,12 +668,13 @@ pub unsafe extern "C" fn pkgcraft_config_add_repo_path(
         false => unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), ptr::null_mut()) },
     };
     let config = null_ptr_check!(config.as_mut());
-    let repo = unwrap_or_return!(config.add_repo_path(id, priority, path), ptr::null_mut());
-    let repo_conf = RepoConfig {
-        id: CString::new(id).unwrap().into_raw(),
-        format: (&repo).into(),
-        repo: Box::into_raw(Box::new(repo)),
-    };
+    let repo = unwrap_or_return!(
+        config.config.add_repo_path(id, priority, path),
+        ptr::null_mut()
+    );
+    let repo = ConfigRepo::new(repo, path);
+    let repo_conf = RepoConfig::new(&repo);
+    config.add_repo(repo);
     Box::into_raw(Box::new(repo_conf))
 }
 
@@ -67,24 +686,18 @@ pub unsafe extern "C" fn pkgcraft_config_add_repo_path(
 /// The path argument should be a valid path on the system.
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_config_load_repos_conf(
-    config: *mut config::Config,
+    config: *mut Config,
     path: *const c_char,
     len: *mut usize,
 ) -> *mut *mut RepoConfig {
     let path = null_ptr_check!(path.as_ref());
     let path = unsafe { unwrap_or_return!(CStr::from_ptr(path).to_str(), ptr::null_mut()) };
     let config = null_ptr_check!(config.as_mut());
-    let repos = unwrap_or_return!(config.load_repos_conf(path), ptr::null_mut());
+    let repos = unwrap_or_return!(config.load_repos_conf(Path::new(path)), ptr::null_mut());
     let mut ptrs: Vec<_> = repos
-        .into_iter()
-        .map(|repo| {
-            let repo_conf = RepoConfig {
-                id: CString::new(repo.id()).unwrap().into_raw(),
-                format: (&repo).into(),
-                repo: Box::into_raw(Box::new(repo)),
-            };
-            Box::into_raw(Box::new(repo_conf))
-        })
+        .iter()
+        .filter_map(|repo| config.repos.iter().find(|r| r.id == repo.id()))
+        .map(|r| Box::into_raw(Box::new(RepoConfig::new(r))))
         .collect();
     ptrs.shrink_to_fit();
     unsafe { *len = ptrs.len() };
@@ -93,28 +706,319 @@ pub unsafe extern "C" fn pkgcraft_config_load_repos_conf(
     p
 }
 
-/// Return the repos for a config.
+/// Return the repos for a config in priority order, highest first.
 ///
 /// # Safety
 /// The config argument must be a non-null Config pointer.
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_config_repos(
-    config: *mut config::Config,
+    config: *mut Config,
     len: *mut usize,
 ) -> *mut *mut RepoConfig {
     // TODO: switch from usize to std::os::raw::c_size_t when it's stable.
     let config = null_ptr_check!(config.as_ref());
     let mut ptrs: Vec<_> = config
         .repos
+        .iter()
+        .map(|r| Box::into_raw(Box::new(RepoConfig::new(r))))
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Return the configured repo for a given id.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The arguments must be a non-null Config pointer and non-null repo id string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_repo_get(
+    config: *mut Config,
+    id: *const c_char,
+) -> *mut RepoConfig {
+    let config = null_ptr_check!(config.as_ref());
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), ptr::null_mut()) };
+    let idx = unwrap_or_return!(config.repo_index(id), ptr::null_mut());
+    Box::into_raw(Box::new(RepoConfig::new(&config.repos[idx])))
+}
+
+/// Return a package iterator over all of a config's repos in priority order, highest first. Pass
+/// NULL for the restrict argument in order to iterate over all packages.
+///
+/// When skip_shadowed is true, packages are skipped if a higher priority repo contains a package
+/// with the same cpv.
+///
+/// The config must not be modified while the iterator is in use.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer and the restrict argument must be a
+/// Restrict pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_iter<'a>(
+    config: *mut Config,
+    restrict: *mut Restrict,
+    skip_shadowed: bool,
+) -> *mut ConfigPkgIter<'a> {
+    let config = null_ptr_check!(config.as_ref());
+    let restrict = unsafe { restrict.as_ref() }.cloned();
+    Box::into_raw(Box::new(ConfigPkgIter::new(
+        config,
+        restrict,
+        skip_shadowed,
+    )))
+}
+
+/// Return the next package from a given config package iterator.
+///
+/// Returns NULL when the iterator is empty.
+///
+/// # Safety
+/// The argument must be a non-null ConfigPkgIter pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_iter_next(i: *mut ConfigPkgIter) -> *mut pkg::Pkg {
+    let iter = null_ptr_check!(i.as_mut());
+    match iter.next() {
+        None => ptr::null_mut(),
+        Some(p) => Box::into_raw(Box::new(p)),
+    }
+}
+
+/// Free a config package iterator.
+///
+/// # Safety
+/// The argument must be a ConfigPkgIter pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_iter_free(i: *mut ConfigPkgIter) {
+    if !i.is_null() {
+        unsafe { drop(Box::from_raw(i)) };
+    }
+}
+
+/// Remove the repo for a given id from a config.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The arguments must be a non-null Config pointer and non-null repo id string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_repo_remove(
+    config: *mut Config,
+    id: *const c_char,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), false) };
+    unwrap_or_return!(config.remove_repo(id), false);
+    true
+}
+
+/// Change the priority of the repo for a given id, reordering the config's repos.
+///
+/// The repo is re-registered using the new priority so previously returned repo pointers keep
+/// their original priority. Repos without a location can't have their priority changed.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The arguments must be a non-null Config pointer and non-null repo id string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_repo_set_priority(
+    config: *mut Config,
+    id: *const c_char,
+    priority: c_int,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), false) };
+    unwrap_or_return!(config.set_repo_priority(id, priority), false);
+    true
+}
+
+/// Set the sync settings for the repo with a given id. Pass NULL for either setting to unset it.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer, the id argument must be a non-null
+/// string, and the remaining arguments must be strings or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_repo_set_sync(
+    config: *mut Config,
+    id: *const c_char,
+    sync_type: *const c_char,
+    sync_uri: *const c_char,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), false) };
+    let sync_type = unwrap_or_return!(unsafe { optional_str(sync_type) }, false);
+    let sync_uri = unwrap_or_return!(unsafe { optional_str(sync_uri) }, false);
+    let idx = unwrap_or_return!(config.repo_index(id), false);
+    let repo = &mut config.repos[idx];
+    repo.sync_type = sync_type;
+    repo.sync_uri = sync_uri;
+    true
+}
+
+/// Set the masters for the repo with a given id. Pass NULL for the masters argument in order to
+/// unset them, falling back to the repo's own settings.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer, the id argument must be a non-null
+/// string, and masters must be an array of len strings or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_repo_set_masters(
+    config: *mut Config,
+    id: *const c_char,
+    masters: *const *const c_char,
+    len: usize,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), false) };
+    let masters = match masters.is_null() {
+        true => None,
+        false => {
+            let masters = unsafe { slice::from_raw_parts(masters, len) };
+            let masters: std::result::Result<Vec<_>, _> = masters
+                .iter()
+                .map(|s| {
+                    let s = null_ptr_check!(s.as_ref());
+                    unsafe { CStr::from_ptr(s).to_str().map(|s| s.to_string()) }
+                })
+                .collect();
+            Some(unwrap_or_return!(masters, false))
+        }
+    };
+    let idx = unwrap_or_return!(config.repo_index(id), false);
+    config.repos[idx].masters = masters;
+    true
+}
+
+/// Callback used to report sync progress, receiving the repo id and a message.
+pub type SyncProgress = extern "C" fn(id: *const c_char, msg: *const c_char, data: *mut c_void);
+
+/// Sync the repo for a given id from its sync URI, supporting local git repos, tarball snapshots,
+/// and directories synced via rsync. The sync type is inferred from the URI if unset.
+///
+/// Existing git checkouts are updated from the sync URI rather than their configured origin.
+/// Tarballs containing a single top-level directory use it as the repo root, otherwise the
+/// tarball's contents are used directly.
+///
+/// Pass NULL for the progress argument in order to disable progress reporting, otherwise it's
+/// called with the given data pointer for status updates and command output. Repos without a
+/// location are synced to the repos subdirectory of the config's data directory.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer and the id argument must be a non-null
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_sync(
+    config: *mut Config,
+    id: *const c_char,
+    progress: Option<SyncProgress>,
+    data: *mut c_void,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let id = null_ptr_check!(id.as_ref());
+    let id_str = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), false) };
+    let report = |msg: &str| {
+        if let Some(func) = progress {
+            let msg = CString::new(msg.replace('\0', "")).unwrap();
+            func(id, msg.as_ptr(), data);
+        }
+    };
+    unwrap_or_return!(config.sync_repo(id_str, report), false);
+    true
+}
+
+/// Save the config's repos to a portage-compatible repos.conf file or directory. Pass NULL for
+/// the path argument in order to use the repos.conf file in the config directory.
+///
+/// Existing files are updated in place, preserving comments, unknown repo settings, and sections
+/// for repos not in the config. Repos without a location are skipped.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer and the path argument must be a path
+/// string or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_save_repos_conf(
+    config: *mut Config,
+    path: *const c_char,
+) -> bool {
+    let config = null_ptr_check!(config.as_ref());
+    let path = unwrap_or_return!(unsafe { self::path(path) }, false);
+    let path = match path {
+        Some(path) => path,
+        None => unwrap_or_return!(config.config_dir(), false).join("repos.conf"),
+    };
+    unwrap_or_return!(config.save_repos_conf(&path), false);
+    true
+}
+
+/// Load portage settings from make.conf, package.use, package.accept_keywords, package.license,
+/// package.mask, and package.unmask in a given config directory. Pass NULL for the path argument
+/// in order to use the system portage config directory, e.g. /etc/portage.
+///
+/// Settings are added to those previously loaded and missing files are ignored.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer and the path argument must be a path
+/// string or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_load_portage(
+    config: *mut Config,
+    path: *const c_char,
+) -> bool {
+    let config = null_ptr_check!(config.as_mut());
+    let path = unwrap_or_return!(unsafe { self::path(path) }, false);
+    let path = path.unwrap_or_else(|| config.portage_dir());
+    unwrap_or_return!(config.portage.load(&path), false);
+    true
+}
+
+/// Return the value of a variable set in the loaded make.conf files.
+///
+/// Returns NULL if the variable is unset.
+///
+/// # Safety
+/// The config argument must be a non-null Config pointer and the name argument must be a
+/// non-null string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_make_conf_var(
+    config: *mut Config,
+    name: *const c_char,
+) -> *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let name = null_ptr_check!(name.as_ref());
+    let name = unsafe { unwrap_or_return!(CStr::from_ptr(name).to_str(), ptr::null_mut()) };
+    match config.portage.var(name) {
+        Some(value) => CString::new(value).unwrap().into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Convert strings into a C string array.
+///
+/// # Safety
+/// The len argument must be a non-null pointer.
+unsafe fn str_array(values: Vec<String>, len: *mut usize) -> *mut *mut c_char {
+    let mut ptrs: Vec<_> = values
         .into_iter()
-        .map(|(id, repo)| {
-            let repo_conf = RepoConfig {
-                id: CString::new(id).unwrap().into_raw(),
-                format: repo.into(),
-                repo,
-            };
-            Box::into_raw(Box::new(repo_conf))
-        })
+        .map(|s| CString::new(s).unwrap().into_raw())
         .collect();
     ptrs.shrink_to_fit();
     unsafe { *len = ptrs.len() };
@@ -123,10 +1027,72 @@ pub unsafe extern "C" fn pkgcraft_config_repos(
     p
 }
 
-/// Free a repo config.
+/// Return the USE flags enabled for a package using USE from make.conf and package.use entries.
 ///
-/// Note that repo pointers aren't explicitly freed since different calls return borrowed or owned
-/// pointers so external users should handle freeing them if necessary via [`pkgcraft_repo_free`].
+/// # Safety
+/// The config and pkg arguments must be non-null Config and Pkg pointers, respectively. The
+/// returned array must be freed via pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_use(
+    config: *mut Config,
+    p: *mut pkg::Pkg,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let pkg = null_ptr_check!(p.as_ref());
+    unsafe { str_array(config.portage.use_flags(pkg), len) }
+}
+
+/// Return the keywords accepted for a package using ACCEPT_KEYWORDS from make.conf and
+/// package.accept_keywords entries.
+///
+/// # Safety
+/// The config and pkg arguments must be non-null Config and Pkg pointers, respectively. The
+/// returned array must be freed via pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_accept_keywords(
+    config: *mut Config,
+    p: *mut pkg::Pkg,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let pkg = null_ptr_check!(p.as_ref());
+    unsafe { str_array(config.portage.accept_keywords(pkg), len) }
+}
+
+/// Return the licenses accepted for a package using ACCEPT_LICENSE from make.conf and
+/// package.license entries.
+///
+/// # Safety
+/// The config and pkg arguments must be non-null Config and Pkg pointers, respectively. The
+/// returned array must be freed via pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_license(
+    config: *mut Config,
+    p: *mut pkg::Pkg,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let config = null_ptr_check!(config.as_ref());
+    let pkg = null_ptr_check!(p.as_ref());
+    unsafe { str_array(config.portage.accept_license(pkg), len) }
+}
+
+/// Determine if a package is masked via package.mask and package.unmask entries.
+///
+/// Matching portage, packages are masked when matching a package.mask entry without matching any
+/// package.unmask entry. Entries such as "-cat/pkg" drop previously loaded entries of the same
+/// type for the atom.
+///
+/// # Safety
+/// The config and pkg arguments must be non-null Config and Pkg pointers, respectively.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_config_pkg_masked(config: *mut Config, p: *mut pkg::Pkg) -> bool {
+    let config = null_ptr_check!(config.as_ref());
+    let pkg = null_ptr_check!(p.as_ref());
+    config.portage.masked(pkg)
+}
+
+/// Free a repo config including its repo.
 ///
 /// # Safety
 /// The argument must be a RepoConfig pointer or NULL.
@@ -136,6 +1102,19 @@ pub unsafe extern "C" fn pkgcraft_repo_config_free(r: *mut RepoConfig) {
         unsafe {
             let repo_conf = Box::from_raw(r);
             drop(CString::from_raw(repo_conf.id));
+            drop(Box::from_raw(repo_conf.repo));
+            drop(CString::from_raw(repo_conf.location));
+            for s in [repo_conf.sync_type, repo_conf.sync_uri] {
+                if !s.is_null() {
+                    drop(CString::from_raw(s));
+                }
+            }
+            if !repo_conf.masters.is_null() {
+                let len = repo_conf.masters_len;
+                for s in Vec::from_raw_parts(repo_conf.masters, len, len) {
+                    drop(CString::from_raw(s));
+                }
+            }
         }
     }
 }
@@ -161,7 +1140,7 @@ pub unsafe extern "C" fn pkgcraft_repos_free(repos: *mut *mut RepoConfig, len: u
 /// # Safety
 /// The argument must be a Config pointer or NULL.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_config_free(config: *mut config::Config) {
+pub unsafe extern "C" fn pkgcraft_config_free(config: *mut Config) {
     if !config.is_null() {
         unsafe { drop(Box::from_raw(config)) };
     }
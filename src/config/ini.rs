@@ -0,0 +1,244 @@
+use std::path::Path;
+use std::{fmt, fs};
+
+use crate::{Error, Result};
+
+/// Line of an INI file section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    /// Key-value entry along with its original text if it hasn't been modified.
+    Entry {
+        key: String,
+        value: String,
+        raw: Option<String>,
+    },
+    /// Blank or comment line.
+    Other(String),
+}
+
+/// Section of an INI file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Section {
+    name: String,
+    header: Option<String>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    pub(super) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            header: None,
+            lines: vec![],
+        }
+    }
+
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the entry for a given key, ignoring case.
+    fn entry_mut(&mut self, key: &str) -> Option<(&mut String, &mut Option<String>)> {
+        self.lines.iter_mut().find_map(|line| match line {
+            Line::Entry { key: k, value, raw } if k.eq_ignore_ascii_case(key) => Some((value, raw)),
+            _ => None,
+        })
+    }
+
+    /// Return the value for a given key, ignoring case.
+    pub(super) fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Entry { key: k, value, .. } if k.eq_ignore_ascii_case(key) => {
+                Some(value.as_str())
+            }
+            _ => None,
+        })
+    }
+
+    /// Set the value for a given key, keeping its position if it already exists.
+    ///
+    /// New entries are added after the existing entries, before any trailing comments.
+    pub(super) fn set(&mut self, key: &str, value: &str) {
+        match self.entry_mut(key) {
+            Some((v, _)) if v == value => (),
+            Some((v, raw)) => {
+                *v = value.to_string();
+                *raw = None;
+            }
+            None => {
+                let idx = self
+                    .lines
+                    .iter()
+                    .rposition(|l| matches!(l, Line::Entry { .. }))
+                    .map(|i| i + 1)
+                    .unwrap_or_default();
+                let entry = Line::Entry {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    raw: None,
+                };
+                self.lines.insert(idx, entry);
+            }
+        }
+    }
+
+    pub(super) fn remove(&mut self, key: &str) {
+        self.lines
+            .retain(|l| !matches!(l, Line::Entry { key: k, .. } if k.eq_ignore_ascii_case(key)));
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.header {
+            Some(header) => writeln!(f, "{header}")?,
+            None => writeln!(f, "[{}]", self.name)?,
+        }
+        for line in &self.lines {
+            match line {
+                Line::Entry { raw: Some(raw), .. } => writeln!(f, "{raw}")?,
+                // continuation lines are indented
+                Line::Entry { key, value, .. } => {
+                    writeln!(f, "{key} = {}", value.replace('\n', "\n\t"))?
+                }
+                Line::Other(s) => writeln!(f, "{s}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Minimal INI file support compatible with the format used by portage's repos.conf files.
+///
+/// Comments, blank lines, and unmodified entries are preserved when files are rewritten.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct Ini {
+    preamble: Vec<String>,
+    sections: Vec<Section>,
+}
+
+impl Ini {
+    pub(super) fn parse(s: &str) -> Result<Self> {
+        let mut ini = Self::default();
+        let mut key: Option<String> = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let err = |msg: &str| Error::new(format!("line {}: {msg}: {line:?}", i + 1));
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                match ini.sections.last_mut() {
+                    Some(section) => section.lines.push(Line::Other(line.to_string())),
+                    None => ini.preamble.push(line.to_string()),
+                }
+            } else if line.starts_with(char::is_whitespace) {
+                // continuation of the previous value
+                let entry = match (ini.sections.last_mut(), &key) {
+                    (Some(section), Some(key)) => section.entry_mut(key),
+                    _ => None,
+                };
+                let (value, raw) = entry.ok_or_else(|| err("unexpected continuation line"))?;
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(trimmed);
+                if let Some(raw) = raw {
+                    raw.push('\n');
+                    raw.push_str(line);
+                }
+            } else if let Some(name) = trimmed.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .ok_or_else(|| err("invalid section"))?
+                    .trim();
+                let mut section = Section::new(name);
+                section.header = Some(line.to_string());
+                ini.sections.push(section);
+                key = None;
+            } else {
+                let section = ini
+                    .sections
+                    .last_mut()
+                    .ok_or_else(|| err("missing section header"))?;
+                let (k, v) = trimmed
+                    .split_once(['=', ':'])
+                    .ok_or_else(|| err("invalid entry"))?;
+                let k = k.trim();
+                match section.entry_mut(k) {
+                    // repeated keys override previous values
+                    Some((value, raw)) => {
+                        *value = v.trim().to_string();
+                        *raw = None;
+                    }
+                    None => section.lines.push(Line::Entry {
+                        key: k.to_string(),
+                        value: v.trim().to_string(),
+                        raw: Some(line.to_string()),
+                    }),
+                }
+                key = Some(k.to_string());
+            }
+        }
+
+        Ok(ini)
+    }
+
+    pub(super) fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| Error::new(format!("failed reading {path:?}: {e}")))?;
+        Self::parse(&data).map_err(|e| Error::new(format!("invalid INI file: {path:?}: {e}")))
+    }
+
+    pub(super) fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| Error::new(format!("failed creating dir {dir:?}: {e}")))?;
+        }
+        fs::write(path, self.to_string())
+            .map_err(|e| Error::new(format!("failed writing {path:?}: {e}")))
+    }
+
+    pub(super) fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    pub(super) fn sections_mut(&mut self) -> &mut [Section] {
+        &mut self.sections
+    }
+
+    /// Return the last section with a given name, matching how duplicate sections override
+    /// previous ones.
+    pub(super) fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().rev().find(|s| s.name == name)
+    }
+
+    /// Return the last mutable section with a given name, adding it if it doesn't exist.
+    pub(super) fn section_mut(&mut self, name: &str) -> &mut Section {
+        let idx = match self.sections.iter().rposition(|s| s.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.sections.push(Section::new(name));
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[idx]
+    }
+}
+
+impl fmt::Display for Ini {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+        for line in &self.preamble {
+            s.push_str(line);
+            s.push('\n');
+        }
+        for section in &self.sections {
+            // separate new sections from previous content
+            if section.header.is_none() && !s.is_empty() && !s.ends_with("\n\n") {
+                s.push('\n');
+            }
+            s.push_str(&section.to_string());
+        }
+        write!(f, "{s}")
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use pkgcraft::restrict::Restriction;
+use pkgcraft::{atom, pkg};
+
+use crate::parse::default_eapi;
+use crate::restrict::Restrict;
+use crate::{Error, Result};
+
+/// Return the files for a given config file or directory, recursing into subdirectories.
+///
+/// Hidden files and backup files ending with '~' are ignored and the remaining files are
+/// returned in sorted order.
+fn config_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let err = |e: std::io::Error| Error::new(format!("failed reading {path:?}: {e}"));
+    let mut paths = vec![];
+    for entry in fs::read_dir(path).map_err(err)? {
+        let entry = entry.map_err(err)?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with('.') && !name.ends_with('~') {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    let mut files = vec![];
+    for path in paths {
+        files.extend(config_files(&path)?);
+    }
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::new(format!("failed reading {path:?}: {e}")))
+}
+
+/// Parser for make.conf files supporting the subset of bash syntax used by portage.
+///
+/// Variables are assigned via `VAR=value` with an optional `export` prefix, values may be single
+/// or double quoted, `$VAR` and `${VAR}` references are expanded outside single quotes, and
+/// other files can be included via `source path` or `. path`.
+struct MakeConfParser<'a> {
+    vars: &'a mut HashMap<String, String>,
+    path: &'a Path,
+}
+
+impl<'a> MakeConfParser<'a> {
+    fn parse(&mut self, data: &str) -> Result<()> {
+        let mut chars = data.chars().peekable();
+        loop {
+            // skip whitespace and comments
+            while let Some(c) = chars.peek() {
+                match c {
+                    '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+                    c if c.is_whitespace() => {
+                        chars.next();
+                    }
+                    _ => break,
+                }
+            }
+            if chars.peek().is_none() {
+                return Ok(());
+            }
+
+            let mut word = self.word(&mut chars);
+            if word == "export" {
+                self.skip_blanks(&mut chars);
+                word = self.word(&mut chars);
+            }
+
+            match (word.as_str(), chars.peek()) {
+                ("source" | ".", Some(' ' | '\t')) => {
+                    self.skip_blanks(&mut chars);
+                    let path = self.value(&mut chars)?;
+                    let path = match self.path.parent() {
+                        Some(dir) => dir.join(path),
+                        None => PathBuf::from(path),
+                    };
+                    MakeConfParser {
+                        vars: self.vars,
+                        path: &path,
+                    }
+                    .parse(&read(&path)?)?;
+                }
+                (name, Some('=')) if valid_var(name) => {
+                    chars.next();
+                    let value = self.value(&mut chars)?;
+                    self.vars.insert(name.to_string(), value);
+                }
+                _ => {
+                    return Err(Error::new(format!(
+                        "invalid make.conf: {:?}: unsupported syntax: {word:?}",
+                        self.path
+                    )))
+                }
+            }
+        }
+    }
+
+    fn skip_blanks(&self, chars: &mut Peekable<Chars>) {
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+    }
+
+    /// Return the next unquoted word, stopping at whitespace or an assignment.
+    fn word(&self, chars: &mut Peekable<Chars>) -> String {
+        let mut word = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            word.push(c);
+        }
+        word
+    }
+
+    /// Return the next value, consisting of quoted and unquoted segments ending at unquoted
+    /// whitespace.
+    fn value(&self, chars: &mut Peekable<Chars>) -> Result<String> {
+        let mut value = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            match c {
+                '\'' => loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(self.error("unterminated single quote")),
+                    }
+                },
+                '"' => loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('\n') => (),
+                            Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => return Err(self.error("unterminated double quote")),
+                        },
+                        Some('$') => value.push_str(&self.expand(chars)?),
+                        Some(c) => value.push(c),
+                        None => return Err(self.error("unterminated double quote")),
+                    }
+                },
+                '\\' => match chars.next() {
+                    Some('\n') | None => (),
+                    Some(c) => value.push(c),
+                },
+                '$' => value.push_str(&self.expand(chars)?),
+                c => value.push(c),
+            }
+        }
+        Ok(value)
+    }
+
+    /// Expand a variable reference following a '$' character.
+    fn expand(&self, chars: &mut Peekable<Chars>) -> Result<String> {
+        let mut name = String::new();
+        if chars.next_if_eq(&'{').is_some() {
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => name.push(c),
+                    None => return Err(self.error("unterminated variable reference")),
+                }
+            }
+            if !valid_var(&name) {
+                return Err(self.error(&format!("unsupported variable reference: ${{{name}}}")));
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                name.push(c);
+            }
+            if name.is_empty() {
+                return Ok("$".to_string());
+            }
+        }
+        Ok(self.vars.get(&name).cloned().unwrap_or_default())
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::new(format!("invalid make.conf: {:?}: {msg}", self.path))
+    }
+}
+
+/// Determine if a string is a valid shell variable name.
+fn valid_var(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Characters starting atoms with version operators.
+const VERSION_OPS: [char; 4] = ['<', '>', '=', '~'];
+
+/// Package config file entry, e.g. a package.use line.
+struct Entry {
+    pattern: String,
+    negated: bool,
+    restrict: Restrict,
+    values: Vec<String>,
+}
+
+/// Parse the entries from a package config file or directory.
+///
+/// Package globs such as "*/*" or "dev-*/*" are supported in addition to atoms. Entries
+/// starting with a version operator are always parsed as atoms so version globs such as
+/// "=dev-lang/python-3.11*" work as expected. Negated entries, e.g. "-cat/pkg", are only
+/// allowed for incremental files.
+fn entries(path: &Path, incremental: bool) -> Result<Vec<Entry>> {
+    let eapi = default_eapi()?;
+    let mut entries = vec![];
+    for file in config_files(path)? {
+        for (i, line) in read(&file)?.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut tokens = line.split_whitespace();
+            let s = match tokens.next() {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let err = |e: &dyn std::fmt::Display| {
+                Error::new(format!("invalid entry: {file:?}, line {}: {e}", i + 1))
+            };
+            let (negated, pattern) = match s.strip_prefix('-') {
+                Some(s) if incremental => (true, s),
+                Some(_) => return Err(err(&"negated entries are unsupported")),
+                None => (false, s),
+            };
+            let restrict = match pattern.contains('*') && !pattern.starts_with(VERSION_OPS) {
+                true => Restrict::glob(pattern).map_err(|e| err(&e))?,
+                false => {
+                    let atom = atom::Atom::new(pattern, eapi).map_err(|e| err(&e))?;
+                    let restrict: pkgcraft::restrict::Restrict = (&atom).into();
+                    restrict.into()
+                }
+            };
+
+            entries.push(Entry {
+                pattern: pattern.to_string(),
+                negated,
+                restrict,
+                values: expand_use_expand(tokens),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Convert USE_EXPAND syntax, e.g. "PYTHON_TARGETS: python3_11", into the related USE flags.
+fn expand_use_expand<'a, I: Iterator<Item = &'a str>>(tokens: I) -> Vec<String> {
+    let mut prefix: Option<String> = None;
+    let mut values = vec![];
+    for token in tokens {
+        if let Some(name) = token.strip_suffix(':') {
+            prefix = Some(name.to_lowercase());
+        } else if let Some(prefix) = &prefix {
+            match token.strip_prefix('-') {
+                Some(val) => values.push(format!("-{prefix}_{val}")),
+                None => values.push(format!("{prefix}_{token}")),
+            }
+        } else {
+            values.push(token.to_string());
+        }
+    }
+    values
+}
+
+/// Incrementally resolve values where "-value" removes a value and "-*" removes all values.
+fn incremental<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Vec<String> {
+    let mut resolved: Vec<String> = vec![];
+    for value in values {
+        match value.strip_prefix('-') {
+            Some("*") => resolved.clear(),
+            Some(val) => resolved.retain(|v| v != val),
+            None => {
+                if !resolved.iter().any(|v| v == value) {
+                    resolved.push(value.to_string());
+                }
+            }
+        }
+    }
+    resolved
+}
+
+/// Portage configuration from make.conf and package.* files.
+#[derive(Default)]
+pub(super) struct PortageConfig {
+    make_conf: HashMap<String, String>,
+    package_use: Vec<Entry>,
+    package_accept_keywords: Vec<Entry>,
+    package_license: Vec<Entry>,
+    package_mask: Vec<Entry>,
+    package_unmask: Vec<Entry>,
+}
+
+impl PortageConfig {
+    /// Load the config from a portage config directory, e.g. /etc/portage.
+    ///
+    /// Settings are added to any previously loaded settings, with nonexistent files ignored.
+    pub(super) fn load(&mut self, dir: &Path) -> Result<()> {
+        let path = dir.join("make.conf");
+        if path.exists() {
+            for file in config_files(&path)? {
+                MakeConfParser {
+                    vars: &mut self.make_conf,
+                    path: &file,
+                }
+                .parse(&read(&file)?)?;
+            }
+        }
+
+        let files = [
+            ("package.use", &mut self.package_use),
+            ("package.accept_keywords", &mut self.package_accept_keywords),
+            ("package.license", &mut self.package_license),
+        ];
+        for (name, values) in files {
+            let path = dir.join(name);
+            if path.exists() {
+                values.extend(entries(&path, false)?);
+            }
+        }
+
+        // mask files are incremental, "-atom" entries removing previously loaded entries
+        let files = [
+            ("package.mask", &mut self.package_mask),
+            ("package.unmask", &mut self.package_unmask),
+        ];
+        for (name, values) in files {
+            let path = dir.join(name);
+            if path.exists() {
+                for e in entries(&path, true)? {
+                    match e.negated {
+                        true => values.retain(|v| v.pattern != e.pattern),
+                        false => values.push(e),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the value of a make.conf variable.
+    pub(super) fn var(&self, name: &str) -> Option<&str> {
+        self.make_conf.get(name).map(|s| s.as_str())
+    }
+
+    /// Incrementally resolve a make.conf variable with the matching package entries applied.
+    fn resolve(&self, var: &str, entries: &[Entry], pkg: &pkg::Pkg) -> Vec<String> {
+        let global = self.var(var).unwrap_or_default().split_whitespace();
+        let pkg_values = entries
+            .iter()
+            .filter(|e| e.restrict.matches(pkg))
+            .flat_map(|e| e.values.iter().map(|s| s.as_str()));
+        incremental(global.chain(pkg_values))
+    }
+
+    /// Return the USE flags enabled for a package.
+    pub(super) fn use_flags(&self, pkg: &pkg::Pkg) -> Vec<String> {
+        self.resolve("USE", &self.package_use, pkg)
+    }
+
+    /// Return the accepted keywords for a package.
+    ///
+    /// Matching package.accept_keywords entries without keywords accept the testing variants of
+    /// the stable keywords accepted via ACCEPT_KEYWORDS.
+    pub(super) fn accept_keywords(&self, pkg: &pkg::Pkg) -> Vec<String> {
+        let global = self.var("ACCEPT_KEYWORDS").unwrap_or_default();
+        let testing: Vec<_> = global
+            .split_whitespace()
+            .filter(|s| !s.starts_with(['-', '~']))
+            .map(|s| format!("~{s}"))
+            .collect();
+
+        let mut values: Vec<&str> = global.split_whitespace().collect();
+        for e in self
+            .package_accept_keywords
+            .iter()
+            .filter(|e| e.restrict.matches(pkg))
+        {
+            match e.values.is_empty() {
+                true => values.extend(testing.iter().map(|s| s.as_str())),
+                false => values.extend(e.values.iter().map(|s| s.as_str())),
+            }
+        }
+        incremental(values)
+    }
+
+    /// Return the accepted licenses for a package.
+    pub(super) fn accept_license(&self, pkg: &pkg::Pkg) -> Vec<String> {
+        self.resolve("ACCEPT_LICENSE", &self.package_license, pkg)
+    }
+
+    /// Determine if a package is masked, matching portage where any matching unmask entry
+    /// overrides all mask entries regardless of the order they were loaded in.
+    pub(super) fn masked(&self, pkg: &pkg::Pkg) -> bool {
+        let matches = |entries: &[Entry]| entries.iter().any(|e| e.restrict.matches(pkg));
+        matches(&self.package_mask) && !matches(&self.package_unmask)
+    }
+}
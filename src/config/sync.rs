@@ -0,0 +1,176 @@
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{fs, process};
+
+use crate::{Error, Result};
+
+/// Supported sync backends for local sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Syncer {
+    Git,
+    Tar,
+    Rsync,
+}
+
+impl Syncer {
+    /// Determine the sync backend for a repo, inferring it from the URI if the type is unset.
+    pub(super) fn new(sync_type: Option<&str>, uri: &Path) -> Result<Self> {
+        let name = uri.to_string_lossy();
+        match sync_type {
+            Some("git") => Ok(Self::Git),
+            Some("tar" | "tarball" | "snapshot") => Ok(Self::Tar),
+            Some("rsync") => Ok(Self::Rsync),
+            Some(s) => Err(Error::new(format!("unsupported sync type: {s}"))),
+            None if name.ends_with(".git") || uri.join(".git").exists() => Ok(Self::Git),
+            None if [".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tar.xz", ".tar.zst"]
+                .iter()
+                .any(|ext| name.ends_with(ext)) =>
+            {
+                Ok(Self::Tar)
+            }
+            None if uri.is_dir() => Ok(Self::Rsync),
+            None => Err(Error::new(format!("unknown sync type for uri: {name}"))),
+        }
+    }
+
+    /// Sync a repo from a local source into a given location.
+    ///
+    /// Progress messages consisting of status updates and the output of the underlying commands
+    /// are passed to the given callback.
+    pub(super) fn sync<F: FnMut(&str)>(
+        &self,
+        uri: &Path,
+        location: &Path,
+        mut progress: F,
+    ) -> Result<()> {
+        if let Some(dir) = location.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| Error::new(format!("failed creating {dir:?}: {e}")))?;
+        }
+
+        match self {
+            Self::Git if location.join(".git").exists() => {
+                progress(&format!("updating {location:?} from {uri:?}"));
+                let mut cmd = Command::new("git");
+                // pull from the configured uri since it may differ from the checkout's origin
+                cmd.arg("-C")
+                    .arg(location)
+                    .args(["pull", "--ff-only", "--progress"])
+                    .arg(uri);
+                run(cmd, &mut progress)?;
+            }
+            Self::Git => {
+                progress(&format!("cloning {uri:?} to {location:?}"));
+                let mut cmd = Command::new("git");
+                cmd.args(["clone", "--progress"]).arg(uri).arg(location);
+                run(cmd, &mut progress)?;
+            }
+            Self::Tar => {
+                // extract to a temporary directory so failures leave existing repos untouched
+                let tmp = temp_path(location, "new");
+                let old = temp_path(location, "old");
+                let _ = fs::remove_dir_all(&tmp);
+                fs::create_dir_all(&tmp)
+                    .map_err(|e| Error::new(format!("failed creating {tmp:?}: {e}")))?;
+
+                progress(&format!("extracting {uri:?} to {location:?}"));
+                let mut cmd = Command::new("tar");
+                cmd.arg("-xf").arg(uri).arg("-C").arg(&tmp);
+                let root = run(cmd, &mut progress).and_then(|_| tar_root(&tmp));
+                let root = match root {
+                    Ok(path) => path,
+                    Err(e) => {
+                        let _ = fs::remove_dir_all(&tmp);
+                        return Err(e);
+                    }
+                };
+
+                let rename = |src: &Path, dest: &Path| {
+                    fs::rename(src, dest).map_err(|e| {
+                        Error::new(format!("failed renaming {src:?} to {dest:?}: {e}"))
+                    })
+                };
+                if location.exists() {
+                    rename(location, &old)?;
+                }
+                rename(&root, location)?;
+                let _ = fs::remove_dir_all(&tmp);
+                let _ = fs::remove_dir_all(&old);
+            }
+            Self::Rsync => {
+                progress(&format!("syncing {uri:?} to {location:?}"));
+                // trailing slashes sync directory contents instead of the directories themselves
+                let mut src = uri.as_os_str().to_os_string();
+                src.push("/");
+                let mut dest = location.as_os_str().to_os_string();
+                dest.push("/");
+                let mut cmd = Command::new("rsync");
+                cmd.args(["-a", "--delete"]).arg(src).arg(dest);
+                run(cmd, &mut progress)?;
+            }
+        }
+
+        progress("finished");
+        Ok(())
+    }
+}
+
+/// Return a temporary path alongside a given path.
+fn temp_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}-{}", process::id()));
+    path.with_file_name(name)
+}
+
+/// Return the repo root for an extracted tarball.
+///
+/// Tarballs containing a single top-level directory, such as gentoo snapshots, use that directory
+/// as the repo root while the contents of all other tarballs are used directly.
+fn tar_root(path: &Path) -> Result<PathBuf> {
+    let entries: std::io::Result<Vec<_>> =
+        fs::read_dir(path).and_then(|entries| entries.map(|e| e.map(|e| e.path())).collect());
+    let entries = entries.map_err(|e| Error::new(format!("failed reading {path:?}: {e}")))?;
+    match &entries[..] {
+        [root] if root.is_dir() => Ok(root.clone()),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Run a command, passing its output lines to the progress callback.
+fn run<F: FnMut(&str)>(mut cmd: Command, progress: &mut F) -> Result<()> {
+    let name = cmd.get_program().to_string_lossy().to_string();
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::new(format!("failed running {name}: {e}")))?;
+
+    // commands output progress info to stderr, often using carriage returns to update lines
+    let mut last = String::new();
+    if let Some(stderr) = child.stderr.take() {
+        for chunk in BufReader::new(stderr).split(b'\n') {
+            let chunk =
+                chunk.map_err(|e| Error::new(format!("failed reading {name} output: {e}")))?;
+            let chunk = String::from_utf8_lossy(&chunk);
+            for line in chunk
+                .split('\r')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+            {
+                progress(line);
+                last = line.to_string();
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| Error::new(format!("failed running {name}: {e}")))?;
+    match status.success() {
+        true => Ok(()),
+        false if last.is_empty() => Err(Error::new(format!("{name} failed: {status}"))),
+        false => Err(Error::new(format!("{name} failed: {last}"))),
+    }
+}
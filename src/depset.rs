@@ -0,0 +1,876 @@
+use std::collections::HashSet;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::str::Utf8Error;
+use std::{fmt, mem, ptr, slice};
+
+use pkgcraft::{atom, eapi};
+
+use crate::macros::*;
+use crate::parse::eapi_or_default;
+use crate::{Error, Result};
+
+mod parse;
+mod required_use;
+mod set;
+
+/// Dependency set variants.
+///
+/// Variants are prefixed in C to avoid clashing with the Restrict and SrcUri types.
+/// cbindgen:prefix-with-name
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum DepSetKind {
+    Dependencies,
+    License,
+    Properties,
+    RequiredUse,
+    Restrict,
+    SrcUri,
+}
+
+/// Dependency tree node variants.
+#[repr(C)]
+pub enum DepRestrictKind {
+    Matches,
+    AllOf,
+    AnyOf,
+    ExactlyOneOf,
+    AtMostOneOf,
+    UseEnabled,
+    UseDisabled,
+}
+
+impl<T> From<&set::DepRestrict<T>> for DepRestrictKind {
+    fn from(restrict: &set::DepRestrict<T>) -> Self {
+        match restrict {
+            set::DepRestrict::Matches(_) => Self::Matches,
+            set::DepRestrict::AllOf(_) => Self::AllOf,
+            set::DepRestrict::AnyOf(_) => Self::AnyOf,
+            set::DepRestrict::ExactlyOneOf(_) => Self::ExactlyOneOf,
+            set::DepRestrict::AtMostOneOf(_) => Self::AtMostOneOf,
+            set::DepRestrict::UseEnabled(..) => Self::UseEnabled,
+            set::DepRestrict::UseDisabled(..) => Self::UseDisabled,
+        }
+    }
+}
+
+/// Dependency sets of all supported value types.
+enum DepSetWrapper {
+    Atom(set::DepSet<atom::Atom>),
+    String(set::DepSet<String>),
+    Uri(set::DepSet<set::Uri>),
+}
+
+/// Opaque wrapper for DepSet objects.
+pub struct DepSet {
+    kind: DepSetKind,
+    set: DepSetWrapper,
+}
+
+impl DepSet {
+    fn atoms(kind: DepSetKind, set: set::DepSet<atom::Atom>) -> Self {
+        Self {
+            kind,
+            set: DepSetWrapper::Atom(set),
+        }
+    }
+
+    fn strings(kind: DepSetKind, set: set::DepSet<String>) -> Self {
+        Self {
+            kind,
+            set: DepSetWrapper::String(set),
+        }
+    }
+
+    fn uris(kind: DepSetKind, set: set::DepSet<set::Uri>) -> Self {
+        Self {
+            kind,
+            set: DepSetWrapper::Uri(set),
+        }
+    }
+}
+
+impl fmt::Display for DepSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.set {
+            DepSetWrapper::Atom(d) => write!(f, "{d}"),
+            DepSetWrapper::String(d) => write!(f, "{d}"),
+            DepSetWrapper::Uri(d) => write!(f, "{d}"),
+        }
+    }
+}
+
+/// Dependency tree nodes of all supported value types.
+enum DepRestrictWrapper {
+    Atom(set::DepRestrict<atom::Atom>),
+    String(set::DepRestrict<String>),
+    Uri(set::DepRestrict<set::Uri>),
+}
+
+/// Opaque wrapper for DepRestrict objects.
+pub struct DepRestrict(DepRestrictWrapper);
+
+impl DepRestrict {
+    fn kind(&self) -> DepRestrictKind {
+        match &self.0 {
+            DepRestrictWrapper::Atom(r) => r.into(),
+            DepRestrictWrapper::String(r) => r.into(),
+            DepRestrictWrapper::Uri(r) => r.into(),
+        }
+    }
+
+    fn use_flag(&self) -> Option<&str> {
+        use set::DepRestrict::*;
+        match &self.0 {
+            DepRestrictWrapper::Atom(UseEnabled(flag, _) | UseDisabled(flag, _)) => Some(flag),
+            DepRestrictWrapper::String(UseEnabled(flag, _) | UseDisabled(flag, _)) => Some(flag),
+            DepRestrictWrapper::Uri(UseEnabled(flag, _) | UseDisabled(flag, _)) => Some(flag),
+            _ => None,
+        }
+    }
+
+    fn children(&self) -> Vec<DepRestrict> {
+        match &self.0 {
+            DepRestrictWrapper::Atom(r) => r.children().iter().map(DepRestrict::from).collect(),
+            DepRestrictWrapper::String(r) => r.children().iter().map(DepRestrict::from).collect(),
+            DepRestrictWrapper::Uri(r) => r.children().iter().map(DepRestrict::from).collect(),
+        }
+    }
+}
+
+impl From<&set::DepRestrict<atom::Atom>> for DepRestrict {
+    fn from(restrict: &set::DepRestrict<atom::Atom>) -> Self {
+        Self(DepRestrictWrapper::Atom(restrict.clone()))
+    }
+}
+
+impl From<&set::DepRestrict<String>> for DepRestrict {
+    fn from(restrict: &set::DepRestrict<String>) -> Self {
+        Self(DepRestrictWrapper::String(restrict.clone()))
+    }
+}
+
+impl From<&set::DepRestrict<set::Uri>> for DepRestrict {
+    fn from(restrict: &set::DepRestrict<set::Uri>) -> Self {
+        Self(DepRestrictWrapper::Uri(restrict.clone()))
+    }
+}
+
+impl fmt::Display for DepRestrict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            DepRestrictWrapper::Atom(r) => write!(f, "{r}"),
+            DepRestrictWrapper::String(r) => write!(f, "{r}"),
+            DepRestrictWrapper::Uri(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+/// REQUIRED_USE clause unsatisfied by a USE configuration.
+#[repr(C)]
+pub struct RequiredUseFailure {
+    clause: *mut c_char,
+    reason: *mut c_char,
+}
+
+/// SRC_URI entry with its related distfile and restrictions.
+#[repr(C)]
+pub struct SrcUri {
+    uri: *mut c_char,
+    filename: *mut c_char,
+    mirror: *mut c_char,
+    fetch_restricted: bool,
+    mirror_restricted: bool,
+}
+
+impl SrcUri {
+    fn new(uri: &set::Uri, restrict: &HashSet<&str>) -> Self {
+        let unrestrict = uri.unrestrict();
+        let fetch = restrict.contains("fetch");
+        let mirror = fetch || restrict.contains("mirror");
+        Self {
+            uri: CString::new(uri.uri()).unwrap().into_raw(),
+            filename: CString::new(uri.filename()).unwrap().into_raw(),
+            mirror: match uri.mirror() {
+                None => ptr::null_mut(),
+                Some(s) => CString::new(s).unwrap().into_raw(),
+            },
+            fetch_restricted: fetch && unrestrict.is_none(),
+            mirror_restricted: mirror && unrestrict != Some(set::Unrestrict::Mirror),
+        }
+    }
+}
+
+/// Convert an array of USE flag strings into a set.
+///
+/// # Safety
+/// The flags argument must be an array of non-null strings or NULL when len is zero.
+unsafe fn use_flags<'a>(
+    flags: *const *const c_char,
+    len: usize,
+) -> std::result::Result<HashSet<&'a str>, Utf8Error> {
+    if flags.is_null() {
+        return Ok(HashSet::new());
+    }
+
+    let flags = unsafe { slice::from_raw_parts(flags, len) };
+    flags
+        .iter()
+        .map(|&s| unsafe { CStr::from_ptr(s).to_str() })
+        .collect()
+}
+
+/// Return the REQUIRED_USE tree for a given DepSet.
+fn required_use(depset: &DepSet) -> Result<&set::DepSet<String>> {
+    match (&depset.kind, &depset.set) {
+        (DepSetKind::RequiredUse, DepSetWrapper::String(d)) => Ok(d),
+        _ => Err(Error::new("DepSet is not a REQUIRED_USE set")),
+    }
+}
+
+/// Depth-first iterators over dependency sets of all supported value types.
+enum DepSetIterWrapper<'a> {
+    Atom(set::Iter<'a, atom::Atom>),
+    String(set::Iter<'a, String>),
+    Uri(set::Iter<'a, set::Uri>),
+}
+
+/// Opaque wrapper for DepSetIter objects.
+pub struct DepSetIter<'a>(DepSetIterWrapper<'a>);
+
+impl Iterator for DepSetIter<'_> {
+    type Item = DepRestrict;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            DepSetIterWrapper::Atom(iter) => iter.next().map(DepRestrict::from),
+            DepSetIterWrapper::String(iter) => iter.next().map(DepRestrict::from),
+            DepSetIterWrapper::Uri(iter) => iter.next().map(DepRestrict::from),
+        }
+    }
+}
+
+/// Parse a dependency string, e.g. DEPEND or RDEPEND, using a specific EAPI. Pass NULL for the
+/// eapi argument in order to parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The dependency argument should be a UTF-8 string while eapi can be a string or may be
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_dependencies(
+    s: *const c_char,
+    eapi: *const c_char,
+) -> *mut DepSet {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
+    unsafe { pkgcraft_depset_dependencies_with_eapi(s, eapi) }
+}
+
+/// Parse a dependency string, e.g. DEPEND or RDEPEND, using a specific EAPI. Pass NULL for the
+/// eapi argument in order to parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The dependency argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_dependencies_with_eapi(
+    s: *const c_char,
+    eapi: *const eapi::Eapi,
+) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, ptr::null_mut());
+    let depset = unwrap_or_return!(parse::dependencies(s, eapi), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::atoms(DepSetKind::Dependencies, depset)))
+}
+
+/// Parse a LICENSE string.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument should be a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_license(s: *const c_char) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let depset = unwrap_or_return!(parse::license(s), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::strings(DepSetKind::License, depset)))
+}
+
+/// Parse a PROPERTIES string.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument should be a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_properties(s: *const c_char) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let depset = unwrap_or_return!(parse::properties(s), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::strings(DepSetKind::Properties, depset)))
+}
+
+/// Parse a REQUIRED_USE string using a specific EAPI. Pass NULL for the eapi argument in order to
+/// parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The REQUIRED_USE argument should be a UTF-8 string while eapi can be a string or may be
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_required_use(
+    s: *const c_char,
+    eapi: *const c_char,
+) -> *mut DepSet {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
+    unsafe { pkgcraft_depset_required_use_with_eapi(s, eapi) }
+}
+
+/// Parse a REQUIRED_USE string using a specific EAPI. Pass NULL for the eapi argument in order to
+/// parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The REQUIRED_USE argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_required_use_with_eapi(
+    s: *const c_char,
+    eapi: *const eapi::Eapi,
+) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, ptr::null_mut());
+    let depset = unwrap_or_return!(parse::required_use(s, eapi), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::strings(DepSetKind::RequiredUse, depset)))
+}
+
+/// Parse a SRC_URI string using a specific EAPI. Pass NULL for the eapi argument in order to
+/// parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The SRC_URI argument should be a UTF-8 string while eapi can be a string or may be
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_src_uri(
+    s: *const c_char,
+    eapi: *const c_char,
+) -> *mut DepSet {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
+    unsafe { pkgcraft_depset_src_uri_with_eapi(s, eapi) }
+}
+
+/// Parse a SRC_URI string using a specific EAPI. Pass NULL for the eapi argument in order to
+/// parse using the latest EAPI with extensions.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The SRC_URI argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_src_uri_with_eapi(
+    s: *const c_char,
+    eapi: *const eapi::Eapi,
+) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, ptr::null_mut());
+    let depset = unwrap_or_return!(parse::src_uri(s, eapi), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::uris(DepSetKind::SrcUri, depset)))
+}
+
+/// Return all the entries of a SRC_URI DepSet, including those under USE conditionals.
+///
+/// The restrict argument is an optional RESTRICT DepSet used to determine the fetch and mirror
+/// restrictions for each entry, taking "fetch+" and "mirror+" prefixes into account. Its USE
+/// conditionals are evaluated using the given enabled USE flags. Pass NULL to ignore
+/// restrictions.
+///
+/// Use pkgcraft_depset_evaluate() beforehand in order to only return applicable entries.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The DepSet argument must be a non-null DepSet pointer created by pkgcraft_depset_src_uri()
+/// while the restrict argument must be a DepSet pointer created by pkgcraft_depset_restrict() or
+/// NULL. The flags argument must be an array of flags_len USE flag strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_src_uris(
+    d: *mut DepSet,
+    restrict: *mut DepSet,
+    flags: *const *const c_char,
+    flags_len: usize,
+    len: *mut usize,
+) -> *mut *mut SrcUri {
+    let depset = null_ptr_check!(d.as_ref());
+    let enabled = unwrap_or_return!(unsafe { use_flags(flags, flags_len) }, ptr::null_mut());
+    let uris = match &depset.set {
+        DepSetWrapper::Uri(d) => d,
+        _ => {
+            let err = Error::new("DepSet is not a SRC_URI set");
+            unwrap_or_return!(Err(err), ptr::null_mut())
+        }
+    };
+
+    let restrict = match unsafe { restrict.as_ref() } {
+        None => None,
+        Some(DepSet {
+            kind: DepSetKind::Restrict,
+            set: DepSetWrapper::String(d),
+        }) => Some(d.evaluate(&enabled, false)),
+        Some(_) => {
+            let err = Error::new("DepSet is not a RESTRICT set");
+            unwrap_or_return!(Err(err), ptr::null_mut())
+        }
+    };
+
+    let restrict: HashSet<_> = restrict
+        .iter()
+        .flat_map(|d| d.iter())
+        .filter_map(|r| match r {
+            set::DepRestrict::Matches(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut ptrs: Vec<_> = uris
+        .iter()
+        .filter_map(|r| match r {
+            set::DepRestrict::Matches(uri) => {
+                Some(Box::into_raw(Box::new(SrcUri::new(uri, &restrict))))
+            }
+            _ => None,
+        })
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Free a SRC_URI entry.
+///
+/// # Safety
+/// The argument must be a SrcUri pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_src_uri_free(u: *mut SrcUri) {
+    if !u.is_null() {
+        unsafe {
+            let uri = Box::from_raw(u);
+            drop(CString::from_raw(uri.uri));
+            drop(CString::from_raw(uri.filename));
+            if !uri.mirror.is_null() {
+                drop(CString::from_raw(uri.mirror));
+            }
+        }
+    }
+}
+
+/// Free an array of SRC_URI entries.
+///
+/// # Safety
+/// The argument must be the value received from pkgcraft_src_uris() or NULL along with the
+/// length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_src_uris_free(uris: *mut *mut SrcUri, len: usize) {
+    if !uris.is_null() {
+        unsafe {
+            for u in Vec::from_raw_parts(uris, len, len).into_iter() {
+                pkgcraft_src_uri_free(u);
+            }
+        }
+    }
+}
+
+/// Determine if a REQUIRED_USE DepSet is satisfied by the given enabled USE flags.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The DepSet argument must be a non-null DepSet pointer created by
+/// pkgcraft_depset_required_use() while flags must be an array of flags_len USE flag strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_required_use_satisfied(
+    d: *mut DepSet,
+    flags: *const *const c_char,
+    flags_len: usize,
+) -> bool {
+    let depset = null_ptr_check!(d.as_ref());
+    let depset = unwrap_or_return!(required_use(depset), false);
+    let enabled = unwrap_or_return!(unsafe { use_flags(flags, flags_len) }, false);
+    required_use::failures(depset, &enabled).is_empty()
+}
+
+/// Return the unsatisfied clauses of a REQUIRED_USE DepSet for the given enabled USE flags along
+/// with human-readable explanations, e.g. the clause "^^ ( a b )" with both flags enabled is
+/// explained as "exactly one of a, b must be satisfied, but 2 are".
+///
+/// Conditional clauses are only checked when active and failures are reported for their nested
+/// clauses. Inactive conditionals inside groups aren't counted as group members, e.g.
+/// "|| ( a? ( b ) )" is unsatisfied when a is disabled. An empty array is returned when the
+/// REQUIRED_USE is satisfied.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The DepSet argument must be a non-null DepSet pointer created by
+/// pkgcraft_depset_required_use() while flags must be an array of flags_len USE flag strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_required_use_failures(
+    d: *mut DepSet,
+    flags: *const *const c_char,
+    flags_len: usize,
+    len: *mut usize,
+) -> *mut *mut RequiredUseFailure {
+    let depset = null_ptr_check!(d.as_ref());
+    let depset = unwrap_or_return!(required_use(depset), ptr::null_mut());
+    let enabled = unwrap_or_return!(unsafe { use_flags(flags, flags_len) }, ptr::null_mut());
+    let mut ptrs: Vec<_> = required_use::failures(depset, &enabled)
+        .into_iter()
+        .map(|f| {
+            let failure = RequiredUseFailure {
+                clause: CString::new(f.clause).unwrap().into_raw(),
+                reason: CString::new(f.reason).unwrap().into_raw(),
+            };
+            Box::into_raw(Box::new(failure))
+        })
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Free a REQUIRED_USE failure.
+///
+/// # Safety
+/// The argument must be a RequiredUseFailure pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_required_use_failure_free(f: *mut RequiredUseFailure) {
+    if !f.is_null() {
+        unsafe {
+            let failure = Box::from_raw(f);
+            drop(CString::from_raw(failure.clause));
+            drop(CString::from_raw(failure.reason));
+        }
+    }
+}
+
+/// Free an array of REQUIRED_USE failures.
+///
+/// # Safety
+/// The argument must be the value received from pkgcraft_required_use_failures() or NULL along
+/// with the length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_required_use_failures_free(
+    failures: *mut *mut RequiredUseFailure,
+    len: usize,
+) {
+    if !failures.is_null() {
+        unsafe {
+            for f in Vec::from_raw_parts(failures, len, len).into_iter() {
+                pkgcraft_required_use_failure_free(f);
+            }
+        }
+    }
+}
+
+/// Parse a RESTRICT string.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument should be a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_restrict(s: *const c_char) -> *mut DepSet {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let depset = unwrap_or_return!(parse::restrict(s), ptr::null_mut());
+    Box::into_raw(Box::new(DepSet::strings(DepSetKind::Restrict, depset)))
+}
+
+/// Return the variant of a given DepSet.
+///
+/// # Safety
+/// The argument must be a non-null DepSet pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_kind(d: *mut DepSet) -> DepSetKind {
+    let depset = null_ptr_check!(d.as_ref());
+    depset.kind
+}
+
+/// Return the string for a given DepSet.
+///
+/// # Safety
+/// The argument must be a non-null DepSet pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_str(d: *mut DepSet) -> *mut c_char {
+    let depset = null_ptr_check!(d.as_ref());
+    CString::new(depset.to_string()).unwrap().into_raw()
+}
+
+/// Evaluate a DepSet for the given enabled USE flags, returning a new DepSet of the same variant.
+///
+/// USE conditionals are resolved and all-of groups are flattened while any-of groups are kept
+/// intact unless collapse_any_of is true, in which case each is replaced by its first choice.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The DepSet argument must be a non-null DepSet pointer while flags must be an array of
+/// flags_len USE flag strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_evaluate(
+    d: *mut DepSet,
+    flags: *const *const c_char,
+    flags_len: usize,
+    collapse_any_of: bool,
+) -> *mut DepSet {
+    let depset = null_ptr_check!(d.as_ref());
+    let enabled = unwrap_or_return!(unsafe { use_flags(flags, flags_len) }, ptr::null_mut());
+    let set = match &depset.set {
+        DepSetWrapper::Atom(d) => DepSetWrapper::Atom(d.evaluate(&enabled, collapse_any_of)),
+        DepSetWrapper::String(d) => DepSetWrapper::String(d.evaluate(&enabled, collapse_any_of)),
+        DepSetWrapper::Uri(d) => DepSetWrapper::Uri(d.evaluate(&enabled, collapse_any_of)),
+    };
+    Box::into_raw(Box::new(DepSet {
+        kind: depset.kind,
+        set,
+    }))
+}
+
+/// Return all the atoms for a given dependency DepSet in order, including those under
+/// conditionals and in any-of groups.
+///
+/// Use pkgcraft_depset_evaluate() beforehand in order to only return applicable atoms.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null DepSet pointer created by pkgcraft_depset_dependencies().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_atoms(
+    d: *mut DepSet,
+    len: *mut usize,
+) -> *mut *mut atom::Atom {
+    let depset = null_ptr_check!(d.as_ref());
+    let atoms = match &depset.set {
+        DepSetWrapper::Atom(d) => d,
+        _ => {
+            let err = Error::new("DepSet is not a dependency set");
+            unwrap_or_return!(Err(err), ptr::null_mut())
+        }
+    };
+
+    let mut ptrs: Vec<_> = atoms
+        .iter()
+        .filter_map(|r| match r {
+            set::DepRestrict::Matches(a) => Some(Box::into_raw(Box::new(a.clone()))),
+            _ => None,
+        })
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Return the top-level nodes for a given DepSet.
+///
+/// # Safety
+/// The argument must be a non-null DepSet pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_restricts(
+    d: *mut DepSet,
+    len: *mut usize,
+) -> *mut *mut DepRestrict {
+    let depset = null_ptr_check!(d.as_ref());
+    let restricts: Vec<_> = match &depset.set {
+        DepSetWrapper::Atom(d) => d.restricts().iter().map(DepRestrict::from).collect(),
+        DepSetWrapper::String(d) => d.restricts().iter().map(DepRestrict::from).collect(),
+        DepSetWrapper::Uri(d) => d.restricts().iter().map(DepRestrict::from).collect(),
+    };
+    let mut ptrs: Vec<_> = restricts
+        .into_iter()
+        .map(|r| Box::into_raw(Box::new(r)))
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Return a depth-first iterator over all the nodes of a given DepSet.
+///
+/// # Safety
+/// The argument must be a non-null DepSet pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_iter<'a>(d: *mut DepSet) -> *mut DepSetIter<'a> {
+    let depset = null_ptr_check!(d.as_ref());
+    let iter = match &depset.set {
+        DepSetWrapper::Atom(d) => DepSetIterWrapper::Atom(d.iter()),
+        DepSetWrapper::String(d) => DepSetIterWrapper::String(d.iter()),
+        DepSetWrapper::Uri(d) => DepSetIterWrapper::Uri(d.iter()),
+    };
+    Box::into_raw(Box::new(DepSetIter(iter)))
+}
+
+/// Return the next node from a given DepSet iterator.
+///
+/// Returns NULL when the iterator is empty.
+///
+/// # Safety
+/// The argument must be a non-null DepSetIter pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_iter_next(i: *mut DepSetIter) -> *mut DepRestrict {
+    let iter = null_ptr_check!(i.as_mut());
+    match iter.next() {
+        None => ptr::null_mut(),
+        Some(r) => Box::into_raw(Box::new(r)),
+    }
+}
+
+/// Free a DepSet iterator.
+///
+/// # Safety
+/// The argument must be a non-null DepSetIter pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_iter_free(i: *mut DepSetIter) {
+    if !i.is_null() {
+        unsafe { drop(Box::from_raw(i)) };
+    }
+}
+
+/// Free a DepSet.
+///
+/// # Safety
+/// The argument must be a DepSet pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_depset_free(d: *mut DepSet) {
+    if !d.is_null() {
+        unsafe { drop(Box::from_raw(d)) };
+    }
+}
+
+/// Return the variant of a given DepRestrict.
+///
+/// # Safety
+/// The argument must be a non-null DepRestrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_kind(r: *mut DepRestrict) -> DepRestrictKind {
+    let restrict = null_ptr_check!(r.as_ref());
+    restrict.kind()
+}
+
+/// Return the atom for a given DepRestrict, e.g. the node "a/b" has an atom of "a/b".
+///
+/// Returns NULL on nonexistence, including for nodes from non-dependency sets.
+///
+/// # Safety
+/// The argument must be a non-null DepRestrict pointer. Also, note that the returned pointer
+/// is borrowed from its related DepRestrict object and should never be freed manually.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_atom(r: *mut DepRestrict) -> *const atom::Atom {
+    let restrict = null_ptr_check!(r.as_ref());
+    match &restrict.0 {
+        DepRestrictWrapper::Atom(set::DepRestrict::Matches(a)) => a,
+        _ => ptr::null(),
+    }
+}
+
+/// Return the USE flag for a given conditional DepRestrict, e.g. the node "!flag? ( a/b )" has a
+/// USE flag of "flag".
+///
+/// Returns NULL on nonexistence.
+///
+/// # Safety
+/// The argument must be a non-null DepRestrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_use_flag(r: *mut DepRestrict) -> *mut c_char {
+    let restrict = null_ptr_check!(r.as_ref());
+    match restrict.use_flag() {
+        None => ptr::null_mut(),
+        Some(flag) => CString::new(flag).unwrap().into_raw(),
+    }
+}
+
+/// Return the nested nodes for a given group or conditional DepRestrict.
+///
+/// Returns NULL on nonexistence.
+///
+/// # Safety
+/// The argument must be a non-null DepRestrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_children(
+    r: *mut DepRestrict,
+    len: *mut usize,
+) -> *mut *mut DepRestrict {
+    let restrict = null_ptr_check!(r.as_ref());
+    match restrict.kind() {
+        DepRestrictKind::Matches => ptr::null_mut(),
+        _ => {
+            let mut ptrs: Vec<_> = restrict
+                .children()
+                .into_iter()
+                .map(|r| Box::into_raw(Box::new(r)))
+                .collect();
+            ptrs.shrink_to_fit();
+            unsafe { *len = ptrs.len() };
+            let p = ptrs.as_mut_ptr();
+            mem::forget(ptrs);
+            p
+        }
+    }
+}
+
+/// Return the string for a given DepRestrict, e.g. the license name for a LICENSE leaf node.
+///
+/// # Safety
+/// The argument must be a non-null DepRestrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_str(r: *mut DepRestrict) -> *mut c_char {
+    let restrict = null_ptr_check!(r.as_ref());
+    CString::new(restrict.to_string()).unwrap().into_raw()
+}
+
+/// Free a DepRestrict.
+///
+/// # Safety
+/// The argument must be a DepRestrict pointer or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restrict_free(r: *mut DepRestrict) {
+    if !r.is_null() {
+        unsafe { drop(Box::from_raw(r)) };
+    }
+}
+
+/// Free an array of DepRestrict pointers.
+///
+/// # Safety
+/// The argument must be the value received from pkgcraft_depset_restricts(),
+/// pkgcraft_dep_restrict_children(), or NULL along with the length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_dep_restricts_free(restricts: *mut *mut DepRestrict, len: usize) {
+    if !restricts.is_null() {
+        unsafe {
+            for r in Vec::from_raw_parts(restricts, len, len).into_iter() {
+                pkgcraft_dep_restrict_free(r);
+            }
+        }
+    }
+}
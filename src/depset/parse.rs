@@ -0,0 +1,72 @@
+use pkgcraft::atom::parse;
+use pkgcraft::{atom, depset, eapi};
+
+use super::set::{DepRestrict, DepSet, Uri};
+use crate::Result;
+
+/// Convert a pkgcraft dependency tree node, converting its values via a given function.
+fn convert_restrict<T, U, F>(restrict: depset::Restrict<T>, f: &F) -> DepRestrict<U>
+where
+    F: Fn(T) -> U,
+{
+    let convert = |vals: Vec<Box<depset::Restrict<T>>>| {
+        vals.into_iter().map(|r| convert_restrict(*r, f)).collect()
+    };
+
+    match restrict {
+        depset::Restrict::Matches(val) => DepRestrict::Matches(f(val)),
+        depset::Restrict::AllOf(vals) => DepRestrict::AllOf(convert(vals)),
+        depset::Restrict::AnyOf(vals) => DepRestrict::AnyOf(convert(vals)),
+        depset::Restrict::ExactlyOneOf(vals) => DepRestrict::ExactlyOneOf(convert(vals)),
+        depset::Restrict::AtMostOneOf(vals) => DepRestrict::AtMostOneOf(convert(vals)),
+        depset::Restrict::UseEnabled(flag, vals) => DepRestrict::UseEnabled(flag, convert(vals)),
+        depset::Restrict::UseDisabled(flag, vals) => DepRestrict::UseDisabled(flag, convert(vals)),
+    }
+}
+
+/// Convert a pkgcraft dependency tree, where empty strings are parsed as nonexistent trees.
+fn convert<T, U, F>(depset: Option<depset::DepSet<T>>, f: F) -> DepSet<U>
+where
+    F: Fn(T) -> U,
+{
+    let vals = depset
+        .into_iter()
+        .flatten()
+        .map(|r| convert_restrict(r, &f))
+        .collect();
+    DepSet::new(vals)
+}
+
+/// Parse a dependency string, e.g. DEPEND or RDEPEND.
+pub(crate) fn dependencies(s: &str, eapi: &'static eapi::Eapi) -> Result<DepSet<atom::Atom>> {
+    Ok(convert(parse::dependencies(s, eapi)?, |a| a))
+}
+
+/// Parse a LICENSE string.
+pub(crate) fn license(s: &str) -> Result<DepSet<String>> {
+    Ok(convert(parse::license(s)?, |s| s))
+}
+
+/// Parse a REQUIRED_USE string.
+pub(crate) fn required_use(s: &str, eapi: &'static eapi::Eapi) -> Result<DepSet<String>> {
+    Ok(convert(parse::required_use(s, eapi)?, |s| s))
+}
+
+/// Parse a RESTRICT string.
+pub(crate) fn restrict(s: &str) -> Result<DepSet<String>> {
+    Ok(convert(parse::restrict(s)?, |s| s))
+}
+
+/// Parse a PROPERTIES string.
+pub(crate) fn properties(s: &str) -> Result<DepSet<String>> {
+    Ok(convert(parse::properties(s)?, |s| s))
+}
+
+/// Parse a SRC_URI string.
+pub(crate) fn src_uri(s: &str, eapi: &'static eapi::Eapi) -> Result<DepSet<Uri>> {
+    let depset = parse::src_uri(s, eapi)?;
+    Ok(convert(depset, |u| Uri {
+        uri: u.uri().to_string(),
+        rename: u.rename().map(|s| s.to_string()),
+    }))
+}
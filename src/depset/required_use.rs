@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use super::set::{DepRestrict, DepSet};
+
+/// REQUIRED_USE clause unsatisfied by a given USE configuration.
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub(crate) clause: String,
+    pub(crate) reason: String,
+}
+
+/// Return the members of a group, leaving out inactive USE conditionals.
+fn active<'a>(
+    vals: &'a [DepRestrict<String>],
+    enabled: &HashSet<&str>,
+) -> Vec<&'a DepRestrict<String>> {
+    use DepRestrict::*;
+    vals.iter()
+        .filter(|r| match r {
+            UseEnabled(flag, _) => enabled.contains(flag.as_str()),
+            UseDisabled(flag, _) => !enabled.contains(flag.as_str()),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Return the number of satisfied active members of a group.
+fn count(vals: &[DepRestrict<String>], enabled: &HashSet<&str>) -> usize {
+    active(vals, enabled)
+        .into_iter()
+        .filter(|r| satisfied(r, enabled))
+        .count()
+}
+
+/// Determine if a REQUIRED_USE node is satisfied by the given enabled USE flags.
+fn satisfied(restrict: &DepRestrict<String>, enabled: &HashSet<&str>) -> bool {
+    use DepRestrict::*;
+    let count = |vals: &[DepRestrict<String>]| count(vals, enabled);
+
+    // empty groups of any type are always satisfied, but groups whose members are all inactive
+    // conditionals have nothing to satisfy any-of or exactly-one-of constraints
+    match restrict {
+        Matches(flag) => match flag.strip_prefix('!') {
+            Some(flag) => !enabled.contains(flag),
+            None => enabled.contains(flag.as_str()),
+        },
+        AllOf(vals) => vals.iter().all(|r| satisfied(r, enabled)),
+        AnyOf(vals) => vals.is_empty() || count(vals) > 0,
+        ExactlyOneOf(vals) => vals.is_empty() || count(vals) == 1,
+        AtMostOneOf(vals) => count(vals) <= 1,
+        UseEnabled(flag, vals) => {
+            !enabled.contains(flag.as_str()) || vals.iter().all(|r| satisfied(r, enabled))
+        }
+        UseDisabled(flag, vals) => {
+            enabled.contains(flag.as_str()) || vals.iter().all(|r| satisfied(r, enabled))
+        }
+    }
+}
+
+/// Return a human-readable explanation for an unsatisfied REQUIRED_USE node.
+fn explain(restrict: &DepRestrict<String>, enabled: &HashSet<&str>) -> String {
+    use DepRestrict::*;
+    let list = |vals: &[DepRestrict<String>]| {
+        active(vals, enabled)
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let count = |vals: &[DepRestrict<String>]| count(vals, enabled);
+
+    match restrict {
+        Matches(flag) => match flag.strip_prefix('!') {
+            Some(flag) => format!("{flag} must be disabled"),
+            None => format!("{flag} must be enabled"),
+        },
+        AllOf(vals) => format!("all of {} must be satisfied", list(vals)),
+        AnyOf(vals) => format!("at least one of {} must be satisfied", list(vals)),
+        ExactlyOneOf(vals) => format!(
+            "exactly one of {} must be satisfied, but {} are",
+            list(vals),
+            count(vals)
+        ),
+        AtMostOneOf(vals) => format!(
+            "at most one of {} may be satisfied, but {} are",
+            list(vals),
+            count(vals)
+        ),
+        UseEnabled(flag, _) => format!("{flag} is enabled so {restrict} must be satisfied"),
+        UseDisabled(flag, _) => format!("{flag} is disabled so {restrict} must be satisfied"),
+    }
+}
+
+/// Collect the unsatisfied nodes for a given sequence, descending into active conditionals.
+fn collect_failures(
+    vals: &[DepRestrict<String>],
+    enabled: &HashSet<&str>,
+    conditions: &mut Vec<String>,
+    failures: &mut Vec<Failure>,
+) {
+    use DepRestrict::*;
+    for restrict in vals {
+        match restrict {
+            UseEnabled(flag, vals) if enabled.contains(flag.as_str()) => {
+                conditions.push(format!("{flag} is enabled"));
+                collect_failures(vals, enabled, conditions, failures);
+                conditions.pop();
+            }
+            UseDisabled(flag, vals) if !enabled.contains(flag.as_str()) => {
+                conditions.push(format!("{flag} is disabled"));
+                collect_failures(vals, enabled, conditions, failures);
+                conditions.pop();
+            }
+            UseEnabled(..) | UseDisabled(..) => (),
+            r if !satisfied(r, enabled) => {
+                let reason = explain(r, enabled);
+                let reason = match conditions.is_empty() {
+                    true => reason,
+                    false => format!("when {}, {reason}", conditions.join(" and ")),
+                };
+                failures.push(Failure {
+                    clause: r.to_string(),
+                    reason,
+                });
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Return the unsatisfied clauses of a REQUIRED_USE tree for the given enabled USE flags.
+pub(crate) fn failures(set: &DepSet<String>, enabled: &HashSet<&str>) -> Vec<Failure> {
+    let mut failures = vec![];
+    collect_failures(set.restricts(), enabled, &mut vec![], &mut failures);
+    failures
+}
@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::fmt;
+
+/// Dependency specification tree node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum DepRestrict<T> {
+    /// Singular value, e.g. an atom or license name.
+    Matches(T),
+    /// All-of group, e.g. "( a/b c/d )".
+    AllOf(Vec<DepRestrict<T>>),
+    /// Any-of group, e.g. "|| ( a/b c/d )".
+    AnyOf(Vec<DepRestrict<T>>),
+    /// Exactly-one-of group, e.g. "^^ ( a b )".
+    ExactlyOneOf(Vec<DepRestrict<T>>),
+    /// At-most-one-of group, e.g. "?? ( a b )".
+    AtMostOneOf(Vec<DepRestrict<T>>),
+    /// Enabled USE flag conditional, e.g. "flag? ( a/b )".
+    UseEnabled(String, Vec<DepRestrict<T>>),
+    /// Disabled USE flag conditional, e.g. "!flag? ( a/b )".
+    UseDisabled(String, Vec<DepRestrict<T>>),
+}
+
+impl<T> DepRestrict<T> {
+    /// Return the nested nodes for a given node, leaf nodes having none.
+    pub(crate) fn children(&self) -> &[DepRestrict<T>] {
+        match self {
+            Self::Matches(_) => &[],
+            Self::AllOf(vals) | Self::AnyOf(vals) => vals,
+            Self::ExactlyOneOf(vals) | Self::AtMostOneOf(vals) => vals,
+            Self::UseEnabled(_, vals) | Self::UseDisabled(_, vals) => vals,
+        }
+    }
+}
+
+impl<T: Clone> DepRestrict<T> {
+    /// Evaluate a sequence of nodes for the given enabled USE flags, resolving conditionals and
+    /// splicing all-of groups into the sequence.
+    fn evaluate(vals: &[Self], enabled: &HashSet<&str>, collapse: bool) -> Vec<Self> {
+        let mut evaluated = vec![];
+        for restrict in vals {
+            match restrict {
+                Self::Matches(_) => evaluated.push(restrict.clone()),
+                Self::AllOf(vals) => evaluated.extend(Self::evaluate(vals, enabled, collapse)),
+                Self::UseEnabled(flag, vals) if enabled.contains(flag.as_str()) => {
+                    evaluated.extend(Self::evaluate(vals, enabled, collapse))
+                }
+                Self::UseDisabled(flag, vals) if !enabled.contains(flag.as_str()) => {
+                    evaluated.extend(Self::evaluate(vals, enabled, collapse))
+                }
+                Self::UseEnabled(..) | Self::UseDisabled(..) => (),
+                Self::AnyOf(vals) => {
+                    let mut choices = Self::choices(vals, enabled, collapse).into_iter();
+                    match (collapse, choices.next()) {
+                        (_, None) => (),
+                        (true, Some(Self::AllOf(vals))) => evaluated.extend(vals),
+                        (true, Some(choice)) => evaluated.push(choice),
+                        (false, Some(choice)) => {
+                            let choices = [choice].into_iter().chain(choices).collect();
+                            evaluated.push(Self::AnyOf(choices));
+                        }
+                    }
+                }
+                Self::ExactlyOneOf(vals) => {
+                    evaluated.push(Self::ExactlyOneOf(Self::choices(vals, enabled, collapse)))
+                }
+                Self::AtMostOneOf(vals) => {
+                    evaluated.push(Self::AtMostOneOf(Self::choices(vals, enabled, collapse)))
+                }
+            }
+        }
+        evaluated
+    }
+
+    /// Evaluate the choices of a group for the given enabled USE flags, wrapping choices that
+    /// evaluate to multiple nodes in all-of groups.
+    fn choices(vals: &[Self], enabled: &HashSet<&str>, collapse: bool) -> Vec<Self> {
+        let mut choices = vec![];
+        for restrict in vals {
+            let mut vals = Self::evaluate(std::slice::from_ref(restrict), enabled, collapse);
+            match vals.len() {
+                0 => (),
+                1 => choices.push(vals.remove(0)),
+                _ => choices.push(Self::AllOf(vals)),
+            }
+        }
+        choices
+    }
+}
+
+/// Render a group of nodes in PMS format.
+fn fmt_group<T: fmt::Display>(f: &mut fmt::Formatter, vals: &[T]) -> fmt::Result {
+    write!(f, "(")?;
+    for val in vals {
+        write!(f, " {val}")?;
+    }
+    write!(f, " )")
+}
+
+impl<T: fmt::Display> fmt::Display for DepRestrict<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Matches(val) => write!(f, "{val}"),
+            Self::AllOf(vals) => fmt_group(f, vals),
+            Self::AnyOf(vals) => {
+                write!(f, "|| ")?;
+                fmt_group(f, vals)
+            }
+            Self::ExactlyOneOf(vals) => {
+                write!(f, "^^ ")?;
+                fmt_group(f, vals)
+            }
+            Self::AtMostOneOf(vals) => {
+                write!(f, "?? ")?;
+                fmt_group(f, vals)
+            }
+            Self::UseEnabled(flag, vals) => {
+                write!(f, "{flag}? ")?;
+                fmt_group(f, vals)
+            }
+            Self::UseDisabled(flag, vals) => {
+                write!(f, "!{flag}? ")?;
+                fmt_group(f, vals)
+            }
+        }
+    }
+}
+
+/// Dependency specification tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct DepSet<T>(Vec<DepRestrict<T>>);
+
+impl<T> DepSet<T> {
+    pub(crate) fn new(vals: Vec<DepRestrict<T>>) -> Self {
+        Self(vals)
+    }
+
+    /// Return the top-level nodes.
+    pub(crate) fn restricts(&self) -> &[DepRestrict<T>] {
+        &self.0
+    }
+
+    /// Evaluate the tree for the given enabled USE flags, resolving conditionals and optionally
+    /// collapsing any-of groups to their first choice.
+    pub(crate) fn evaluate(&self, enabled: &HashSet<&str>, collapse: bool) -> Self
+    where
+        T: Clone,
+    {
+        Self(DepRestrict::evaluate(&self.0, enabled, collapse))
+    }
+
+    /// Return a depth-first iterator over all nodes.
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.iter().rev().collect())
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DepSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, val) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{val}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Depth-first iterator over the nodes of a dependency specification tree.
+#[derive(Debug)]
+pub(crate) struct Iter<'a, T>(Vec<&'a DepRestrict<T>>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a DepRestrict<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.0.pop()?;
+        self.0.extend(val.children().iter().rev());
+        Some(val)
+    }
+}
+
+/// SRC_URI entry, e.g. "https://a.com/b.tar.gz -> c.tar.gz".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Uri {
+    pub(crate) uri: String,
+    pub(crate) rename: Option<String>,
+}
+
+/// SRC_URI entry prefixes lifting package restrictions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Unrestrict {
+    /// The "fetch+" prefix, lifting fetch restrictions while staying mirror restricted.
+    Fetch,
+    /// The "mirror+" prefix, lifting both fetch and mirror restrictions.
+    Mirror,
+}
+
+impl Uri {
+    /// Return the unrestriction prefix and the URI without it.
+    fn split_prefix(&self) -> (Option<Unrestrict>, &str) {
+        if let Some(uri) = self.uri.strip_prefix("fetch+") {
+            (Some(Unrestrict::Fetch), uri)
+        } else if let Some(uri) = self.uri.strip_prefix("mirror+") {
+            (Some(Unrestrict::Mirror), uri)
+        } else {
+            (None, &self.uri)
+        }
+    }
+
+    /// Return the unrestriction prefix, if any.
+    pub(crate) fn unrestrict(&self) -> Option<Unrestrict> {
+        self.split_prefix().0
+    }
+
+    /// Return the URI without any unrestriction prefix.
+    pub(crate) fn uri(&self) -> &str {
+        self.split_prefix().1
+    }
+
+    /// Return the mirror name for mirror:// URIs, e.g. "gentoo" for "mirror://gentoo/a.tar.gz".
+    pub(crate) fn mirror(&self) -> Option<&str> {
+        self.uri()
+            .strip_prefix("mirror://")
+            .and_then(|s| s.split('/').next())
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Return the distfile name, using the renamed target if it exists.
+    pub(crate) fn filename(&self) -> &str {
+        match &self.rename {
+            Some(name) => name,
+            None => self.uri().rsplit('/').next().unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.rename {
+            Some(name) => write!(f, "{} -> {name}", self.uri),
+            None => write!(f, "{}", self.uri),
+        }
+    }
+}
@@ -1,15 +1,239 @@
+use std::cmp::Ordering;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
+use std::ops::{Bound, RangeBounds};
+use std::os::raw::{c_char, c_int};
 use std::str::FromStr;
-use std::{mem, ptr};
+use std::{fs, mem, ptr};
 
-use pkgcraft::eapi;
+use once_cell::sync::Lazy;
+use pkgcraft::{eapi, utils::hash};
+use regex::Regex;
 
 use crate::macros::*;
+use crate::Error;
+
+mod pms;
 
 /// Opaque wrapper for Eapi objects.
 pub struct Eapi;
 
+/// EAPI features.
+///
+/// Variants map directly onto pkgcraft's features so mismatches fail to build. They're prefixed
+/// in C since their names are too generic to be unique.
+/// cbindgen:prefix-with-name
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EapiFeature {
+    // EAPI 0
+    RdependDefault,
+    TrailingSlash,
+
+    // EAPI 1
+    IuseDefaults,
+    SlotDeps,
+
+    // EAPI 2
+    Blockers,
+    DomanLangDetect,
+    SrcUriRenames,
+    UseDeps,
+
+    // EAPI 4
+    DodocRecursive,
+    DomanLangOverride,
+    RequiredUse,
+    UseDepDefaults,
+
+    // EAPI 5
+    EbuildPhaseFunc,
+    NewSupportsStdin,
+    ParallelTests,
+    RequiredUseOneOf,
+    SlotOps,
+    Subslots,
+
+    // EAPI 6
+    NonfatalDie,
+    Failglob,
+    UnpackExtendedPath,
+    UnpackCaseInsensitive,
+
+    // EAPI 7
+    ExportDesttree,
+    ExportInsdesttree,
+
+    // EAPI 8
+    ConsistentFileOpts,
+    DosymRelative,
+    SrcUriUnrestrict,
+    UsevTwoArgs,
+}
+
+impl EapiFeature {
+    /// All known features in order of introduction.
+    const ALL: &'static [Self] = &[
+        Self::RdependDefault,
+        Self::TrailingSlash,
+        Self::IuseDefaults,
+        Self::SlotDeps,
+        Self::Blockers,
+        Self::DomanLangDetect,
+        Self::SrcUriRenames,
+        Self::UseDeps,
+        Self::DodocRecursive,
+        Self::DomanLangOverride,
+        Self::RequiredUse,
+        Self::UseDepDefaults,
+        Self::EbuildPhaseFunc,
+        Self::NewSupportsStdin,
+        Self::ParallelTests,
+        Self::RequiredUseOneOf,
+        Self::SlotOps,
+        Self::Subslots,
+        Self::NonfatalDie,
+        Self::Failglob,
+        Self::UnpackExtendedPath,
+        Self::UnpackCaseInsensitive,
+        Self::ExportDesttree,
+        Self::ExportInsdesttree,
+        Self::ConsistentFileOpts,
+        Self::DosymRelative,
+        Self::SrcUriUnrestrict,
+        Self::UsevTwoArgs,
+    ];
+
+    /// Determine if an EAPI supports the feature.
+    fn supported(&self, eapi: &eapi::Eapi) -> bool {
+        eapi.has((*self).into())
+    }
+}
+
+impl From<EapiFeature> for eapi::Feature {
+    fn from(feature: EapiFeature) -> Self {
+        use EapiFeature::*;
+        match feature {
+            RdependDefault => Self::RdependDefault,
+            TrailingSlash => Self::TrailingSlash,
+            IuseDefaults => Self::IuseDefaults,
+            SlotDeps => Self::SlotDeps,
+            Blockers => Self::Blockers,
+            DomanLangDetect => Self::DomanLangDetect,
+            SrcUriRenames => Self::SrcUriRenames,
+            UseDeps => Self::UseDeps,
+            DodocRecursive => Self::DodocRecursive,
+            DomanLangOverride => Self::DomanLangOverride,
+            RequiredUse => Self::RequiredUse,
+            UseDepDefaults => Self::UseDepDefaults,
+            EbuildPhaseFunc => Self::EbuildPhaseFunc,
+            NewSupportsStdin => Self::NewSupportsStdin,
+            ParallelTests => Self::ParallelTests,
+            RequiredUseOneOf => Self::RequiredUseOneOf,
+            SlotOps => Self::SlotOps,
+            Subslots => Self::Subslots,
+            NonfatalDie => Self::NonfatalDie,
+            Failglob => Self::Failglob,
+            UnpackExtendedPath => Self::UnpackExtendedPath,
+            UnpackCaseInsensitive => Self::UnpackCaseInsensitive,
+            ExportDesttree => Self::ExportDesttree,
+            ExportInsdesttree => Self::ExportInsdesttree,
+            ConsistentFileOpts => Self::ConsistentFileOpts,
+            DosymRelative => Self::DosymRelative,
+            SrcUriUnrestrict => Self::SrcUriUnrestrict,
+            UsevTwoArgs => Self::UsevTwoArgs,
+        }
+    }
+}
+
+/// Return the position of an EAPI in the ordered set of all EAPIs.
+fn index(eapi: &eapi::Eapi) -> crate::Result<usize> {
+    eapi::EAPIS
+        .get_index_of(eapi.as_str())
+        .ok_or_else(|| Error::new(format!("unregistered EAPI: {}", eapi.as_str())))
+}
+
+/// Return the ordered EAPIs matching a range expression.
+///
+/// Supported expressions are single EAPIs, operator-prefixed EAPIs (e.g. ">=7"), and Rust-style
+/// ranges (e.g. "6..8", "6..=8", "..7", or "7.."). Ranges with their bounds reversed are
+/// rejected.
+fn range(s: &str) -> crate::Result<Vec<&'static eapi::Eapi>> {
+    let idx = |s: &str| -> crate::Result<usize> { index(eapi::get_eapi(s)?) };
+    let bounds = if let Some((start, end)) = s.split_once("..") {
+        let start = match start {
+            "" => Bound::Unbounded,
+            s => Bound::Included(idx(s)?),
+        };
+        let end = match end.strip_prefix('=') {
+            Some(s) => Bound::Included(idx(s)?),
+            None if end.is_empty() => Bound::Unbounded,
+            None => Bound::Excluded(idx(end)?),
+        };
+        let first = match start {
+            Bound::Included(i) => i,
+            Bound::Excluded(i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let last = match end {
+            Bound::Included(i) => i + 1,
+            Bound::Excluded(i) => i,
+            Bound::Unbounded => eapi::EAPIS.len(),
+        };
+        if first > last {
+            return Err(Error::new(format!("invalid EAPI range: {s}")));
+        }
+        (start, end)
+    } else if let Some(s) = s.strip_prefix(">=") {
+        (Bound::Included(idx(s)?), Bound::Unbounded)
+    } else if let Some(s) = s.strip_prefix("<=") {
+        (Bound::Unbounded, Bound::Included(idx(s)?))
+    } else if let Some(s) = s.strip_prefix('>') {
+        (Bound::Excluded(idx(s)?), Bound::Unbounded)
+    } else if let Some(s) = s.strip_prefix('<') {
+        (Bound::Unbounded, Bound::Excluded(idx(s)?))
+    } else {
+        let i = idx(s.strip_prefix('=').unwrap_or(s))?;
+        (Bound::Included(i), Bound::Included(i))
+    };
+
+    Ok(eapi::EAPIS
+        .values()
+        .enumerate()
+        .filter(|(i, _)| bounds.contains(i))
+        .map(|(_, e)| *e)
+        .collect())
+}
+
+static EAPI_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^[ \t]*EAPI=(?:'([A-Za-z0-9+_.-]*)'|"([A-Za-z0-9+_.-]*)"|([A-Za-z0-9+_.-]*))[ \t]*(?:[ \t]#.*)?$"#,
+    )
+    .unwrap()
+});
+
+/// Determine the EAPI of ebuild content following the rules described by PMS.
+///
+/// The EAPI assignment must occur on the first line that isn't blank or a comment, otherwise
+/// EAPI 0 is assumed. Empty assignments also denote EAPI 0.
+fn from_ebuild_content(content: &str) -> crate::Result<&'static eapi::Eapi> {
+    let line = content.lines().find(|s| {
+        let s = s.trim_start_matches([' ', '\t']);
+        !s.is_empty() && !s.starts_with('#')
+    });
+
+    let s = match line.map(|s| (s, EAPI_RE.captures(s))) {
+        Some((_, Some(caps))) => match caps.iter().skip(1).flatten().next().unwrap().as_str() {
+            "" => "0",
+            s => s,
+        },
+        Some((s, None)) if s.trim_start().starts_with("EAPI=") => {
+            return Err(Error::new(format!("invalid EAPI assignment: {s:?}")));
+        }
+        _ => "0",
+    };
+    Ok(eapi::get_eapi(s)?)
+}
+
 /// Get all known EAPIS.
 ///
 /// # Safety
@@ -46,11 +270,38 @@ pub unsafe extern "C" fn pkgcraft_eapis_official(len: *mut usize) -> *mut *const
     p
 }
 
+/// Get all EAPIs matching a given range expression.
+///
+/// Supported expressions include single EAPIs (e.g. "7"), EAPIs prefixed with comparison
+/// operators (e.g. ">=7"), and ranges (e.g. "6..8" excluding 8 or "6..=8" including it).
+/// Ranges with reversed bounds such as "8..6" are invalid.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null string. The returned array must be freed via
+/// pkgcraft_eapis_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapis_range(
+    s: *const c_char,
+    len: *mut usize,
+) -> *mut *const eapi::Eapi {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let eapis = unwrap_or_return!(range(s), ptr::null_mut());
+    let mut ptrs: Vec<_> = eapis.into_iter().map(|e| e as *const _).collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
 /// Free an array of borrowed Eapi pointers.
 ///
 /// # Safety
-/// The argument must be the value received from pkgcraft_eapis(), pkgcraft_eapis_official(), or
-/// NULL along with the length of the array.
+/// The argument must be the value received from pkgcraft_eapis(), pkgcraft_eapis_official(),
+/// pkgcraft_eapis_range(), or NULL along with the length of the array.
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_eapis_free(eapis: *mut *const eapi::Eapi, len: usize) {
     if !eapis.is_null() {
@@ -71,6 +322,35 @@ pub unsafe extern "C" fn pkgcraft_get_eapi(s: *const c_char) -> *const eapi::Eap
     unwrap_or_return!(eapi::get_eapi(s), ptr::null())
 }
 
+/// Get the EAPI of a given ebuild file without sourcing it.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null path string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_from_ebuild(path: *const c_char) -> *const eapi::Eapi {
+    let path = null_ptr_check!(path.as_ref());
+    let path = unsafe { unwrap_or_return!(CStr::from_ptr(path).to_str(), ptr::null()) };
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::new(format!("failed reading ebuild: {path:?}: {e}")));
+    let content = unwrap_or_return!(content, ptr::null());
+    unwrap_or_return!(from_ebuild_content(&content), ptr::null())
+}
+
+/// Get the EAPI of given ebuild content.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_from_str(s: *const c_char) -> *const eapi::Eapi {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null()) };
+    unwrap_or_return!(from_ebuild_content(s), ptr::null())
+}
+
 /// Check if an EAPI has a given feature.
 ///
 /// # Safety
@@ -84,6 +364,258 @@ pub unsafe extern "C" fn pkgcraft_eapi_has(eapi: *const eapi::Eapi, s: *const c_
     eapi.has(feature)
 }
 
+/// Check if an EAPI has a given feature.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_has_feature(
+    eapi: *const eapi::Eapi,
+    feature: EapiFeature,
+) -> bool {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    feature.supported(eapi)
+}
+
+/// Return all the features supported by an EAPI in order of introduction.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_eapi_features_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_features(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut EapiFeature {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    let mut features: Vec<_> = EapiFeature::ALL
+        .iter()
+        .copied()
+        .filter(|f| f.supported(eapi))
+        .collect();
+    features.shrink_to_fit();
+    unsafe { *len = features.len() };
+    let p = features.as_mut_ptr();
+    mem::forget(features);
+    p
+}
+
+/// Free an array of EAPI features.
+///
+/// # Safety
+/// The argument must be the value received from pkgcraft_eapi_features() or NULL along with the
+/// length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_features_free(features: *mut EapiFeature, len: usize) {
+    if !features.is_null() {
+        unsafe { Vec::from_raw_parts(features, len, len) };
+    }
+}
+
+/// Return the name of a given EAPI feature as accepted by pkgcraft_eapi_has().
+#[no_mangle]
+pub extern "C" fn pkgcraft_eapi_feature_str(feature: EapiFeature) -> *mut c_char {
+    let feature: eapi::Feature = feature.into();
+    CString::new(feature.as_ref()).unwrap().into_raw()
+}
+
+/// Return the names of all known EAPI features in order of introduction.
+///
+/// # Safety
+/// The returned array must be freed via pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_feature_names(len: *mut usize) -> *mut *mut c_char {
+    let mut ptrs: Vec<_> = EapiFeature::ALL
+        .iter()
+        .map(|f| {
+            let feature: eapi::Feature = (*f).into();
+            CString::new(feature.as_ref()).unwrap().into_raw()
+        })
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Compare two EAPIs returning -1, 0, or 1 if the first EAPI is less than, equal to, or greater
+/// than the second EAPI, respectively.
+///
+/// Returns -2 on error.
+///
+/// # Safety
+/// The arguments must be non-null Eapi pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_cmp(e1: *const eapi::Eapi, e2: *const eapi::Eapi) -> c_int {
+    let eapi1 = null_ptr_check!(e1.as_ref());
+    let eapi2 = null_ptr_check!(e2.as_ref());
+
+    let idx1 = unwrap_or_return!(index(eapi1), -2);
+    let idx2 = unwrap_or_return!(index(eapi2), -2);
+
+    match idx1.cmp(&idx2) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// Return the hash value for a given EAPI.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_hash(eapi: *const eapi::Eapi) -> u64 {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    hash(eapi.as_str())
+}
+
+/// Convert EAPI-dependent values into a string array sorted by name.
+///
+/// # Safety
+/// The len argument must be a non-null pointer.
+unsafe fn str_array<I, T>(values: I, len: *mut usize) -> *mut *mut c_char
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    let mut names: Vec<_> = values.into_iter().map(|s| s.as_ref().to_string()).collect();
+    names.sort();
+    let mut ptrs: Vec<_> = names
+        .into_iter()
+        .map(|s| CString::new(s).unwrap().into_raw())
+        .collect();
+    ptrs.shrink_to_fit();
+    unsafe { *len = ptrs.len() };
+    let p = ptrs.as_mut_ptr();
+    mem::forget(ptrs);
+    p
+}
+
+/// Return the phase functions supported by an EAPI.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_phases(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    unsafe { str_array(eapi.phases(), len) }
+}
+
+/// Return the metadata keys an EAPI requires ebuilds to define.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_mandatory_keys(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    unsafe { str_array(eapi.mandatory_keys(), len) }
+}
+
+/// Return the metadata keys an EAPI allows ebuilds to optionally define.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_optional_keys(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    let mandatory = eapi.mandatory_keys();
+    let keys = eapi
+        .metadata_keys()
+        .iter()
+        .filter(|k| !mandatory.contains(*k));
+    unsafe { str_array(keys, len) }
+}
+
+/// Return the dependency metadata keys supported by an EAPI.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_dep_keys(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    unsafe { str_array(eapi.dep_keys(), len) }
+}
+
+/// Return the metadata keys an EAPI accumulates across eclasses and the ebuild.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_incremental_keys(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    unsafe { str_array(eapi.incremental_keys(), len) }
+}
+
+/// Return the archive file extensions an EAPI supports unpacking.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_archives(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    unsafe { str_array(eapi.archives(), len) }
+}
+
+/// Return the commands banned in an EAPI.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_banned_commands(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    let commands = unwrap_or_return!(pms::names(pms::BANNED_COMMANDS, eapi), ptr::null_mut());
+    unsafe { str_array(commands, len) }
+}
+
+/// Return the commands deprecated in an EAPI that are still allowed.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null Eapi pointer. The returned array must be freed via
+/// pkgcraft_str_array_free().
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_eapi_deprecated_commands(
+    eapi: *const eapi::Eapi,
+    len: *mut usize,
+) -> *mut *mut c_char {
+    let eapi = null_ptr_check!(eapi.as_ref());
+    let commands = unwrap_or_return!(pms::names(pms::DEPRECATED_COMMANDS, eapi), ptr::null_mut());
+    unsafe { str_array(commands, len) }
+}
+
 /// Return an EAPI's identifier.
 ///
 /// # Safety
@@ -0,0 +1,72 @@
+use pkgcraft::eapi::{self, Eapi};
+
+/// EAPI-dependent command, supported starting with one EAPI and optionally ending before another.
+///
+/// pkgcraft doesn't expose which commands are banned or deprecated per EAPI so they're tracked
+/// here following PMS.
+pub(super) struct Entry {
+    name: &'static str,
+    since: &'static str,
+    until: Option<&'static str>,
+}
+
+const fn entry(name: &'static str, since: &'static str) -> Entry {
+    Entry {
+        name,
+        since,
+        until: None,
+    }
+}
+
+const fn entry_until(name: &'static str, since: &'static str, until: &'static str) -> Entry {
+    Entry {
+        name,
+        since,
+        until: Some(until),
+    }
+}
+
+impl Entry {
+    /// Determine if an EAPI lies within the entry's range of EAPIs.
+    fn supported(&self, eapi: &Eapi) -> crate::Result<bool> {
+        let idx = |s: &str| super::index(eapi::get_eapi(s)?);
+        let eapi = super::index(eapi)?;
+        let until = match self.until {
+            Some(s) => eapi < idx(s)?,
+            None => true,
+        };
+        Ok(eapi >= idx(self.since)? && until)
+    }
+}
+
+/// Return the names of all entries applying to a given EAPI.
+pub(super) fn names(entries: &[Entry], eapi: &Eapi) -> crate::Result<Vec<&'static str>> {
+    let mut names = vec![];
+    for e in entries {
+        if e.supported(eapi)? {
+            names.push(e.name);
+        }
+    }
+    Ok(names)
+}
+
+/// Commands that are banned, i.e. those that can't be used within ebuilds.
+pub(super) static BANNED_COMMANDS: &[Entry] = &[
+    entry("dohard", "4"),
+    entry("dosed", "4"),
+    entry("einstall", "6"),
+    entry("dohtml", "7"),
+    entry("dolib", "7"),
+    entry("libopts", "7"),
+    entry("hasq", "8"),
+    entry("hasv", "8"),
+    entry("useq", "8"),
+];
+
+/// Commands that are deprecated, i.e. those that are still allowed but will be banned.
+pub(super) static DEPRECATED_COMMANDS: &[Entry] = &[
+    entry_until("dohtml", "6", "7"),
+    entry_until("hasq", "7", "8"),
+    entry_until("hasv", "7", "8"),
+    entry_until("useq", "7", "8"),
+];
@@ -5,87 +5,291 @@ use std::ptr;
 use pkgcraft::{atom, eapi};
 
 use crate::macros::*;
+use crate::{Error, Result};
 
 /// Parse an atom string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The atom argument should be a UTF-8 string while eapi can be a string or may be
 /// NULL to use the default EAPI.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_atom(
+pub unsafe extern "C" fn pkgcraft_parse_atom(atom: *const c_char, eapi: *const c_char) -> bool {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), false);
+    unsafe { pkgcraft_parse_atom_with_eapi(atom, eapi) }
+}
+
+/// Parse an atom string.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The atom argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_atom_with_eapi(
     atom: *const c_char,
-    eapi: *const c_char,
-) -> *const c_char {
+    eapi: *const eapi::Eapi,
+) -> bool {
     let s = null_ptr_check!(atom.as_ref());
-    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), ptr::null_mut());
-    unwrap_or_return!(atom::Atom::valid(s, eapi), ptr::null_mut());
-    atom
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, false);
+    unwrap_or_return!(atom::Atom::valid(s, eapi), false);
+    true
 }
 
 /// Parse an atom category string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The argument should point to a UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_category(s: *const c_char) -> *const c_char {
-    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    unwrap_or_return!(atom::parse::category(val), ptr::null_mut());
-    s
+pub unsafe extern "C" fn pkgcraft_parse_category(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(atom::parse::category(val), false);
+    true
 }
 
 /// Parse an atom package string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The argument should point to a UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_package(s: *const c_char) -> *const c_char {
-    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    unwrap_or_return!(atom::parse::package(val), ptr::null_mut());
-    s
+pub unsafe extern "C" fn pkgcraft_parse_package(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(atom::parse::package(val), false);
+    true
 }
 
 /// Parse an atom version string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The argument should point to a UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_version(s: *const c_char) -> *const c_char {
-    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    unwrap_or_return!(atom::Version::valid(val), ptr::null_mut());
-    s
+pub unsafe extern "C" fn pkgcraft_parse_version(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(atom::Version::valid(val), false);
+    true
 }
 
 /// Parse an atom repo string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The argument should point to a UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_repo(s: *const c_char) -> *const c_char {
-    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    unwrap_or_return!(atom::parse::repo(val), ptr::null_mut());
-    s
+pub unsafe extern "C" fn pkgcraft_parse_repo(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(atom::parse::repo(val), false);
+    true
 }
 
 /// Parse an atom cpv string.
 ///
-/// Returns NULL on error.
+/// Returns false on error.
 ///
 /// # Safety
 /// The argument should point to a UTF-8 string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_parse_cpv(s: *const c_char) -> *const c_char {
-    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
-    unwrap_or_return!(atom::Atom::valid_cpv(val), ptr::null_mut());
-    s
+pub unsafe extern "C" fn pkgcraft_parse_cpv(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(atom::Atom::valid_cpv(val), false);
+    true
+}
+
+/// Parse a USE flag string, e.g. "flag".
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The argument should point to a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_use_flag(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(use_flag(val), false);
+    true
+}
+
+/// Parse a KEYWORDS entry string, e.g. "~amd64" or "-*".
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The argument should point to a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_keyword(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(keyword(val), false);
+    true
+}
+
+/// Parse a license name string, e.g. "GPL-2+".
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The argument should point to a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_license(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(license(val), false);
+    true
+}
+
+/// Parse an eclass name string, e.g. "toolchain-funcs".
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The argument should point to a UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_eclass(s: *const c_char) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    unwrap_or_return!(eclass(val), false);
+    true
+}
+
+/// Parse an IUSE entry string, e.g. "+flag", where default prefixes are only allowed in EAPIs
+/// supporting IUSE defaults.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The IUSE argument should be a UTF-8 string while eapi can be a string or may be
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_iuse(s: *const c_char, eapi: *const c_char) -> bool {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), false);
+    unsafe { pkgcraft_parse_iuse_with_eapi(s, eapi) }
+}
+
+/// Parse an IUSE entry string, e.g. "+flag", where default prefixes are only allowed in EAPIs
+/// supporting IUSE defaults.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The IUSE argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_iuse_with_eapi(
+    s: *const c_char,
+    eapi: *const eapi::Eapi,
+) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, false);
+    unwrap_or_return!(iuse(val, eapi), false);
+    true
+}
+
+/// Parse a SLOT string, e.g. "0/1.2", where subslots are only allowed in EAPIs supporting them.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The slot argument should be a UTF-8 string while eapi can be a string or may be
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_slot(s: *const c_char, eapi: *const c_char) -> bool {
+    let eapi = unwrap_or_return!(eapi::IntoEapi::into_eapi(eapi), false);
+    unsafe { pkgcraft_parse_slot_with_eapi(s, eapi) }
+}
+
+/// Parse a SLOT string, e.g. "0/1.2", where subslots are only allowed in EAPIs supporting them.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The slot argument should be a UTF-8 string while eapi must be an Eapi pointer or
+/// NULL to use the default EAPI.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_parse_slot_with_eapi(
+    s: *const c_char,
+    eapi: *const eapi::Eapi,
+) -> bool {
+    let s = null_ptr_check!(s.as_ref());
+    let val = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    let eapi = unwrap_or_return!(unsafe { eapi_or_default(eapi) }, false);
+    unwrap_or_return!(slot(val, eapi), false);
+    true
+}
+
+/// Return the EAPI used for parsing when none is specified.
+pub(crate) fn default_eapi() -> Result<&'static eapi::Eapi> {
+    // pkgcraft resolves missing EAPIs to its default
+    Ok(eapi::IntoEapi::into_eapi(ptr::null::<c_char>())?)
+}
+
+/// Convert an Eapi pointer into a reference, using the default EAPI for NULL.
+///
+/// # Safety
+/// The argument must be an Eapi pointer or NULL.
+pub(crate) unsafe fn eapi_or_default(eapi: *const eapi::Eapi) -> Result<&'static eapi::Eapi> {
+    match unsafe { eapi.as_ref() } {
+        Some(eapi) => Ok(eapi),
+        None => default_eapi(),
+    }
+}
+
+/// Validate a USE flag name.
+pub(crate) fn use_flag(s: &str) -> Result<&str> {
+    Ok(atom::parse::use_flag(s)?)
+}
+
+/// Validate a KEYWORDS entry, optionally prefixed by a stable or disabled marker.
+pub(crate) fn keyword(s: &str) -> Result<&str> {
+    Ok(atom::parse::keyword(s)?)
+}
+
+/// Validate a license name.
+pub(crate) fn license(s: &str) -> Result<&str> {
+    Ok(atom::parse::license_name(s)?)
+}
+
+/// Validate an eclass name.
+pub(crate) fn eclass(s: &str) -> Result<&str> {
+    Ok(atom::parse::eclass_name(s)?)
+}
+
+/// Validate an IUSE entry, returning the related USE flag name.
+pub(crate) fn iuse<'a>(s: &'a str, eapi: &eapi::Eapi) -> Result<&'a str> {
+    let flag = match s.strip_prefix(['+', '-']) {
+        Some(flag) if eapi.has(eapi::Feature::IuseDefaults) => flag,
+        Some(_) => {
+            let eapi = eapi.as_str();
+            return Err(Error::new(format!(
+                "IUSE defaults unsupported in EAPI {eapi}: {s:?}"
+            )));
+        }
+        None => s,
+    };
+    use_flag(flag)
+}
+
+/// Validate a SLOT value, including an optional subslot.
+pub(crate) fn slot<'a>(s: &'a str, eapi: &eapi::Eapi) -> Result<&'a str> {
+    if s.contains('/') && !eapi.has(eapi::Feature::Subslots) {
+        let eapi = eapi.as_str();
+        return Err(Error::new(format!(
+            "subslots unsupported in EAPI {eapi}: {s:?}"
+        )));
+    }
+    Ok(atom::parse::slot(s)?)
 }
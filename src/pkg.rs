@@ -6,6 +6,7 @@ use pkgcraft::pkg::Package;
 use pkgcraft::{atom, eapi, pkg, repo, restrict, utils::hash, Error};
 
 use crate::macros::*;
+use crate::restrict::Restrict;
 
 pub mod ebuild;
 
@@ -105,9 +106,10 @@ pub unsafe extern "C" fn pkgcraft_pkg_hash(p: *mut pkg::Pkg) -> u64 {
 /// # Safety
 /// The argument must be a non-null Pkg pointer.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_pkg_restrict(p: *mut pkg::Pkg) -> *mut restrict::Restrict {
+pub unsafe extern "C" fn pkgcraft_pkg_restrict(p: *mut pkg::Pkg) -> *mut Restrict {
     let pkg = null_ptr_check!(p.as_ref());
-    Box::into_raw(Box::new(pkg.into()))
+    let restrict: restrict::Restrict = pkg.into();
+    Box::into_raw(Box::new(restrict.into()))
 }
 
 /// Free an package.
@@ -5,11 +5,14 @@ use std::ptr;
 use std::sync::Arc;
 
 use pkgcraft::repo::Repository;
-use pkgcraft::{pkg, repo, restrict, utils::hash, Error};
+use pkgcraft::restrict::Restriction;
+use pkgcraft::{pkg, repo, utils::hash, Error};
 
 use crate::macros::*;
+use crate::restrict::Restrict;
 
 pub mod ebuild;
+pub mod fake;
 
 // explicitly force symbols to be exported
 // TODO: https://github.com/rust-lang/rfcs/issues/2771
@@ -17,8 +20,6 @@ pub mod ebuild;
 pub struct Repo;
 /// Opaque wrapper for PkgIter objects.
 pub struct PkgIter;
-/// Opaque wrapper for RestrictPkgIter objects.
-pub struct RestrictPkgIter;
 
 #[repr(C)]
 pub enum RepoFormat {
@@ -27,6 +28,37 @@ pub enum RepoFormat {
     Empty,
 }
 
+/// Opaque wrapper for RestrictPkgIter objects.
+///
+/// Native restrictions are handled by the repo while any remaining callback or string
+/// restrictions filter the packages it returns.
+pub enum RestrictPkgIter<'a> {
+    Native(repo::RestrictPkgIter<'a>),
+    Filter(repo::RestrictPkgIter<'a>, Restrict),
+}
+
+impl<'a> RestrictPkgIter<'a> {
+    pub(crate) fn new(repo: &'a repo::Repo, restrict: &Restrict) -> Self {
+        let (native, filter) = restrict.split_native();
+        let iter = repo.iter_restrict(native);
+        match filter {
+            None => Self::Native(iter),
+            Some(r) => Self::Filter(iter, r),
+        }
+    }
+}
+
+impl<'a> Iterator for RestrictPkgIter<'a> {
+    type Item = pkg::Pkg<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Native(iter) => iter.next(),
+            Self::Filter(iter, restrict) => iter.find(|p| restrict.matches(p)),
+        }
+    }
+}
+
 impl From<&repo::Repo> for RepoFormat {
     fn from(repo: &repo::Repo) -> Self {
         match repo {
@@ -155,11 +187,11 @@ pub unsafe extern "C" fn pkgcraft_repo_iter_free(i: *mut repo::PkgIter) {
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_repo_restrict_iter<'a>(
     repo: *mut repo::Repo,
-    restrict: *mut restrict::Restrict,
-) -> *mut repo::RestrictPkgIter<'a> {
+    restrict: *mut Restrict,
+) -> *mut RestrictPkgIter<'a> {
     let repo = null_ptr_check!(repo.as_ref());
     let restrict = null_ptr_check!(restrict.as_ref());
-    Box::into_raw(Box::new(repo.iter_restrict(restrict.clone())))
+    Box::into_raw(Box::new(RestrictPkgIter::new(repo, restrict)))
 }
 
 /// Return the next package from a given restriction package iterator.
@@ -170,7 +202,7 @@ pub unsafe extern "C" fn pkgcraft_repo_restrict_iter<'a>(
 /// The argument must be a non-null RestrictPkgIter pointer.
 #[no_mangle]
 pub unsafe extern "C" fn pkgcraft_repo_restrict_iter_next(
-    i: *mut repo::RestrictPkgIter,
+    i: *mut RestrictPkgIter,
 ) -> *mut pkg::Pkg {
     let iter = null_ptr_check!(i.as_mut());
     match iter.next() {
@@ -184,7 +216,7 @@ pub unsafe extern "C" fn pkgcraft_repo_restrict_iter_next(
 /// # Safety
 /// The argument must be a non-null RestrictPkgIter pointer or NULL.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_repo_restrict_iter_free(i: *mut repo::RestrictPkgIter) {
+pub unsafe extern "C" fn pkgcraft_repo_restrict_iter_free(i: *mut RestrictPkgIter) {
     if !i.is_null() {
         unsafe { drop(Box::from_raw(i)) };
     }
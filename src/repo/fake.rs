@@ -0,0 +1,115 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::sync::Arc;
+use std::{ptr, slice};
+
+use pkgcraft::pkg::Package;
+use pkgcraft::repo::{fake, Repo, Repository};
+
+use crate::macros::*;
+use crate::{Error, Result};
+
+/// Convert an array of C strings into string slices.
+///
+/// # Safety
+/// The cpvs argument must be an array of len non-null strings.
+unsafe fn strs<'a>(cpvs: *const *const c_char, len: usize) -> Result<Vec<&'a str>> {
+    if len == 0 {
+        return Ok(vec![]);
+    }
+    let cpvs = unsafe { slice::from_raw_parts(cpvs, len) };
+    cpvs.iter()
+        .map(|s| match unsafe { s.as_ref() } {
+            None => Err(Error::new("unexpected NULL cpv")),
+            Some(s) => unsafe { CStr::from_ptr(s) }
+                .to_str()
+                .map_err(|e| Error::new(format!("invalid cpv: {e}"))),
+        })
+        .collect()
+}
+
+/// Rebuild a fake repo using its current cpvs altered by a given function.
+fn rebuild<F: FnOnce(&mut Vec<String>)>(repo: &mut Repo, func: F) -> Result<()> {
+    if !matches!(repo, Repo::Fake(_)) {
+        return Err(Error::new("invalid repo format"));
+    }
+
+    let mut cpvs: Vec<_> = repo.iter().map(|p| p.atom().cpv()).collect();
+    func(&mut cpvs);
+    let new = fake::Repo::new(repo.id(), repo.priority(), cpvs.iter().map(|s| s.as_str()))?;
+    *repo = Repo::Fake(Arc::new(new));
+    Ok(())
+}
+
+/// Create a fake repo from an array of cpv strings.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The id argument must be a non-null string and cpvs must be an array of len non-null cpv
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_repo_fake_new(
+    id: *const c_char,
+    priority: c_int,
+    cpvs: *const *const c_char,
+    len: usize,
+) -> *mut Repo {
+    let id = null_ptr_check!(id.as_ref());
+    let id = unsafe { unwrap_or_return!(CStr::from_ptr(id).to_str(), ptr::null_mut()) };
+    let cpvs = unwrap_or_return!(unsafe { strs(cpvs, len) }, ptr::null_mut());
+    let repo = unwrap_or_return!(fake::Repo::new(id, priority, cpvs), ptr::null_mut());
+    Box::into_raw(Box::new(Repo::Fake(Arc::new(repo))))
+}
+
+/// Add packages to a fake repo from an array of cpv strings.
+///
+/// The repo is rebuilt so any packages or iterators from it must be freed beforehand.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The repo argument must be a non-null Repo pointer and cpvs must be an array of len non-null
+/// cpv strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_repo_fake_add(
+    r: *mut Repo,
+    cpvs: *const *const c_char,
+    len: usize,
+) -> bool {
+    let repo = null_ptr_check!(r.as_mut());
+    let cpvs = unwrap_or_return!(unsafe { strs(cpvs, len) }, false);
+    let result = rebuild(repo, |existing| {
+        for cpv in cpvs {
+            if !existing.iter().any(|s| s == cpv) {
+                existing.push(cpv.to_string());
+            }
+        }
+    });
+    unwrap_or_return!(result, false);
+    true
+}
+
+/// Remove packages from a fake repo using an array of cpv strings.
+///
+/// The repo is rebuilt so any packages or iterators from it must be freed beforehand.
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The repo argument must be a non-null Repo pointer and cpvs must be an array of len non-null
+/// cpv strings.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_repo_fake_remove(
+    r: *mut Repo,
+    cpvs: *const *const c_char,
+    len: usize,
+) -> bool {
+    let repo = null_ptr_check!(r.as_mut());
+    let cpvs = unwrap_or_return!(unsafe { strs(cpvs, len) }, false);
+    let result = rebuild(repo, |existing| {
+        existing.retain(|s| !cpvs.contains(&s.as_str()))
+    });
+    unwrap_or_return!(result, false);
+    true
+}
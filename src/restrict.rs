@@ -1,13 +1,554 @@
-use std::ffi::CStr;
+use std::borrow::Cow;
+use std::ffi::{c_void, CStr, CString};
+use std::hash::{Hash, Hasher};
 use std::os::raw::c_char;
-use std::ptr;
+use std::{fmt, mem, ptr, slice};
 
-use pkgcraft::restrict;
+use pkgcraft::pkg::Package;
+use pkgcraft::restrict::{self, Restriction};
+use pkgcraft::{atom, pkg, utils::hash};
+use regex::Regex;
 
 use crate::macros::*;
+use crate::Error;
+
+/// Callback invoked per package, returning true for matching packages.
+///
+/// The package pointer is borrowed and only valid for the duration of the call.
+pub type PkgCallback = extern "C" fn(p: *const pkg::Pkg, data: *mut c_void) -> bool;
+
+/// Package callback restriction with its related userdata.
+#[derive(Clone, Copy)]
+pub struct CallbackRestrict {
+    func: PkgCallback,
+    data: *mut c_void,
+}
+
+impl CallbackRestrict {
+    fn call(&self, pkg: &pkg::Pkg) -> bool {
+        (self.func)(pkg, self.data)
+    }
+}
+
+impl fmt::Debug for CallbackRestrict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "callback({:#x}, {:?})", self.func as usize, self.data)
+    }
+}
+
+impl PartialEq for CallbackRestrict {
+    fn eq(&self, other: &Self) -> bool {
+        self.func as usize == other.func as usize && self.data == other.data
+    }
+}
+
+impl Eq for CallbackRestrict {}
+
+impl Hash for CallbackRestrict {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.func as usize).hash(state);
+        self.data.hash(state);
+    }
+}
+
+/// Package fields supporting string restrictions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StrField {
+    Category,
+    Package,
+    Description,
+}
+
+impl fmt::Display for StrField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Category => write!(f, "category"),
+            Self::Package => write!(f, "package"),
+            Self::Description => write!(f, "description"),
+        }
+    }
+}
+
+/// Regex or glob restriction on a package field.
+#[derive(Debug, Clone)]
+pub struct StrRestrict {
+    field: StrField,
+    glob: bool,
+    pattern: String,
+    regex: Regex,
+}
+
+impl StrRestrict {
+    fn regex(field: StrField, pattern: &str) -> crate::Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| Error::new(format!("invalid regex: {pattern:?}: {e}")))?;
+        Ok(Self {
+            field,
+            glob: false,
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// Create a restriction from a glob pattern where '*' matches any number of characters
+    /// and '?' matches a single character.
+    fn glob(field: StrField, pattern: &str) -> crate::Result<Self> {
+        let mut s = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => s.push_str(".*"),
+                '?' => s.push('.'),
+                c => s.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        s.push('$');
+
+        let regex =
+            Regex::new(&s).map_err(|e| Error::new(format!("invalid glob: {pattern:?}: {e}")))?;
+        Ok(Self {
+            field,
+            glob: true,
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    fn is_match(&self, s: &str) -> bool {
+        self.regex.is_match(s)
+    }
+}
+
+impl PartialEq for StrRestrict {
+    fn eq(&self, other: &Self) -> bool {
+        self.field == other.field && self.glob == other.glob && self.pattern == other.pattern
+    }
+}
+
+impl Eq for StrRestrict {}
+
+impl Hash for StrRestrict {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.field.hash(state);
+        self.glob.hash(state);
+        self.pattern.hash(state);
+    }
+}
+
+impl fmt::Display for StrRestrict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = if self.glob { "glob" } else { "=~" };
+        write!(f, "{} {op} {:?}", self.field, self.pattern)
+    }
+}
 
 /// Opaque wrapper for Restrict objects.
-pub struct Restrict;
+///
+/// Native restrictions and combinations of them are passed through to pkgcraft while
+/// combinations including callback or string restrictions are evaluated locally.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Restrict {
+    And(Vec<Restrict>),
+    Or(Vec<Restrict>),
+    Not(Box<Restrict>),
+    Callback(CallbackRestrict),
+    Str(StrRestrict),
+    Native(restrict::Restrict),
+}
+
+impl Restrict {
+    const TRUE: Self = Self::Native(restrict::Restrict::True);
+    const FALSE: Self = Self::Native(restrict::Restrict::False);
+
+    /// Create a restriction matching if all the given restrictions match.
+    pub(crate) fn and(vals: Vec<Self>) -> Self {
+        match Self::natives(&vals) {
+            Some(vals) => Self::Native(restrict::Restrict::and(vals)),
+            None => Self::And(vals),
+        }
+    }
+
+    /// Create a restriction matching if any of the given restrictions match.
+    pub(crate) fn or(vals: Vec<Self>) -> Self {
+        match Self::natives(&vals) {
+            Some(vals) => Self::Native(restrict::Restrict::or(vals)),
+            None => Self::Or(vals),
+        }
+    }
+
+    /// Create a restriction inverting the given restriction.
+    pub(crate) fn not(r: Self) -> Self {
+        match r {
+            Self::Native(r) => Self::Native(restrict::Restrict::not(r)),
+            r => Self::Not(Box::new(r)),
+        }
+    }
+
+    /// Return the native restrictions for the given values if all of them are native.
+    fn natives(vals: &[Self]) -> Option<Vec<restrict::Restrict>> {
+        vals.iter()
+            .map(|r| match r {
+                Self::Native(r) => Some(r.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Return a given restriction with native and/or/not restrictions split into local nodes
+    /// wrapping their native children.
+    fn node(&self) -> Cow<'_, Self> {
+        let wrap = |vals: &[Box<restrict::Restrict>]| {
+            vals.iter()
+                .map(|r| Self::Native(r.as_ref().clone()))
+                .collect()
+        };
+
+        match self {
+            Self::Native(restrict::Restrict::And(vals)) => Cow::Owned(Self::And(wrap(vals))),
+            Self::Native(restrict::Restrict::Or(vals)) => Cow::Owned(Self::Or(wrap(vals))),
+            Self::Native(restrict::Restrict::Not(r)) => {
+                Cow::Owned(Self::Not(Box::new(Self::Native(r.as_ref().clone()))))
+            }
+            r => Cow::Borrowed(r),
+        }
+    }
+
+    /// Return the inverse of a given restriction, dropping double negations.
+    fn negate(&self) -> Self {
+        match self.node().as_ref() {
+            Self::Not(r) => r.as_ref().clone(),
+            _ => Self::not(self.clone()),
+        }
+    }
+
+    /// Split a restriction into the native part used for repo iteration and the remaining part
+    /// that must be matched against each of the resulting packages.
+    pub(crate) fn split_native(&self) -> (restrict::Restrict, Option<Self>) {
+        match self {
+            Self::Native(r) => (r.clone(), None),
+            Self::And(vals) => {
+                let mut native = vec![];
+                let mut other = vec![];
+                for r in vals {
+                    match r {
+                        Self::Native(r) => native.push(r.clone()),
+                        r => other.push(r.clone()),
+                    }
+                }
+                let native = match native.len() {
+                    0 => restrict::Restrict::True,
+                    1 => native.remove(0),
+                    _ => restrict::Restrict::and(native),
+                };
+                (native, Some(Self::and(other)))
+            }
+            r => (restrict::Restrict::True, Some(r.clone())),
+        }
+    }
+
+    /// Return a simplified, logically equivalent restriction.
+    ///
+    /// Nested and/or restrictions are flattened, duplicate and constant terms are removed, and
+    /// double negations are dropped.
+    fn simplify(&self) -> Self {
+        match self.node().as_ref() {
+            Self::And(vals) => Self::simplify_group(vals, true),
+            Self::Or(vals) => Self::simplify_group(vals, false),
+            Self::Not(r) => match r.simplify() {
+                r if r == Self::TRUE => Self::FALSE,
+                r if r == Self::FALSE => Self::TRUE,
+                r => r.negate(),
+            },
+            r => r.clone(),
+        }
+    }
+
+    /// Simplify an and or or group of restrictions.
+    fn simplify_group(vals: &[Self], and: bool) -> Self {
+        // values that can be dropped and those that determine the entire group
+        let (identity, absorbing) = match and {
+            true => (Self::TRUE, Self::FALSE),
+            false => (Self::FALSE, Self::TRUE),
+        };
+
+        let mut restricts: Vec<Self> = vec![];
+        for r in vals.iter().map(|r| r.simplify()) {
+            let nested = match r.node().into_owned() {
+                Self::And(vals) if and => vals,
+                Self::Or(vals) if !and => vals,
+                r => vec![r],
+            };
+            for r in nested {
+                if r == absorbing {
+                    return absorbing;
+                } else if r != identity && !restricts.contains(&r) {
+                    // a term alongside its negation determines the entire group
+                    if restricts.contains(&r.negate()) {
+                        return absorbing;
+                    }
+                    restricts.push(r);
+                }
+            }
+        }
+
+        match restricts.len() {
+            0 => identity,
+            1 => restricts.remove(0),
+            _ if and => Self::and(restricts),
+            _ => Self::or(restricts),
+        }
+    }
+
+    /// Create a restriction matching package category and name globs, e.g. "dev-*/*-utils".
+    pub(crate) fn glob(s: &str) -> crate::Result<Self> {
+        let (cat, pkg) = s
+            .split_once('/')
+            .ok_or_else(|| Error::new(format!("invalid glob: {s:?}: missing category")))?;
+        let cat = StrRestrict::glob(StrField::Category, cat)?;
+        let pkg = StrRestrict::glob(StrField::Package, pkg)?;
+        Ok(Self::and(vec![Self::Str(cat), Self::Str(pkg)]))
+    }
+
+    /// Return a given restriction's direct children.
+    fn children(&self) -> Vec<Self> {
+        match self.node().into_owned() {
+            Self::And(vals) | Self::Or(vals) => vals,
+            Self::Not(r) => vec![*r],
+            _ => vec![],
+        }
+    }
+}
+
+/// Render a native string restriction on a given field.
+fn fmt_native_str(f: &mut fmt::Formatter, field: &str, r: &restrict::Str) -> fmt::Result {
+    match r {
+        restrict::Str::Equal(s) => write!(f, "{field} == {s:?}"),
+        restrict::Str::Prefix(s) => write!(f, "{field} prefix {s:?}"),
+        restrict::Str::Regex(re) => write!(f, "{field} =~ \"{re}\""),
+        restrict::Str::Substr(s) => write!(f, "{field} contains {s:?}"),
+        restrict::Str::Suffix(s) => write!(f, "{field} suffix {s:?}"),
+    }
+}
+
+/// Render an optional native string restriction on a given field.
+fn fmt_native_opt_str(
+    f: &mut fmt::Formatter,
+    field: &str,
+    r: Option<&restrict::Str>,
+) -> fmt::Result {
+    match r {
+        Some(r) => fmt_native_str(f, field, r),
+        None => write!(f, "{field} is none"),
+    }
+}
+
+/// Render a group of restrictions joined by a given operator.
+fn fmt_group<T, F>(f: &mut fmt::Formatter, vals: &[T], op: &str, fmt_val: F) -> fmt::Result
+where
+    F: Fn(&mut fmt::Formatter, &T) -> fmt::Result,
+{
+    write!(f, "(")?;
+    for (i, r) in vals.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{op}")?;
+        }
+        fmt_val(f, r)?;
+    }
+    write!(f, ")")
+}
+
+/// Render a native restriction.
+fn fmt_native(f: &mut fmt::Formatter, r: &restrict::Restrict) -> fmt::Result {
+    match r {
+        restrict::Restrict::True => write!(f, "true"),
+        restrict::Restrict::False => write!(f, "false"),
+        restrict::Restrict::Atom(r) => fmt_native_atom(f, r),
+        restrict::Restrict::Str(r) => fmt_native_str(f, "str", r),
+        restrict::Restrict::And(vals) => fmt_group(f, vals, " && ", |f, r| fmt_native(f, r)),
+        restrict::Restrict::Or(vals) => fmt_group(f, vals, " || ", |f, r| fmt_native(f, r)),
+        restrict::Restrict::Xor(vals) => fmt_group(f, vals, " ^^ ", |f, r| fmt_native(f, r)),
+        restrict::Restrict::Not(r) => {
+            write!(f, "!")?;
+            fmt_native(f, r)
+        }
+    }
+}
+
+/// Render a native atom restriction.
+fn fmt_native_atom(f: &mut fmt::Formatter, r: &atom::Restrict) -> fmt::Result {
+    match r {
+        atom::Restrict::Category(r) => fmt_native_str(f, "category", r),
+        atom::Restrict::Package(r) => fmt_native_str(f, "package", r),
+        atom::Restrict::Blocker(Some(atom::Blocker::Strong)) => write!(f, "blocker == \"!!\""),
+        atom::Restrict::Blocker(Some(atom::Blocker::Weak)) => write!(f, "blocker == \"!\""),
+        atom::Restrict::Blocker(None) => write!(f, "blocker is none"),
+        atom::Restrict::Version(Some(v)) => write!(f, "version {v}"),
+        atom::Restrict::Version(None) => write!(f, "version is none"),
+        atom::Restrict::Slot(r) => fmt_native_opt_str(f, "slot", r.as_ref()),
+        atom::Restrict::Subslot(r) => fmt_native_opt_str(f, "subslot", r.as_ref()),
+        atom::Restrict::UseDeps(Some(flags)) => {
+            let mut flags: Vec<_> = flags.iter().map(|s| s.as_str()).collect();
+            flags.sort_unstable();
+            write!(f, "use deps {:?}", flags.join(","))
+        }
+        atom::Restrict::UseDeps(None) => write!(f, "use deps is none"),
+        atom::Restrict::Repo(r) => fmt_native_opt_str(f, "repo", r.as_ref()),
+    }
+}
+
+impl fmt::Display for Restrict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::And(vals) => fmt_group(f, vals, " && ", |f, r| write!(f, "{r}")),
+            Self::Or(vals) => fmt_group(f, vals, " || ", |f, r| write!(f, "{r}")),
+            Self::Not(r) => write!(f, "!{r}"),
+            Self::Callback(cb) => write!(f, "{cb:?}"),
+            Self::Str(r) => write!(f, "{r}"),
+            Self::Native(r) => fmt_native(f, r),
+        }
+    }
+}
+
+/// Restriction node kinds.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestrictKind {
+    True,
+    False,
+    And,
+    Or,
+    Not,
+    Callback,
+    Glob,
+    Regex,
+    Native,
+}
+
+impl From<&Restrict> for RestrictKind {
+    fn from(r: &Restrict) -> Self {
+        match r.node().as_ref() {
+            Restrict::And(_) => Self::And,
+            Restrict::Or(_) => Self::Or,
+            Restrict::Not(_) => Self::Not,
+            Restrict::Callback(_) => Self::Callback,
+            Restrict::Str(r) if r.glob => Self::Glob,
+            Restrict::Str(_) => Self::Regex,
+            Restrict::Native(restrict::Restrict::True) => Self::True,
+            Restrict::Native(restrict::Restrict::False) => Self::False,
+            Restrict::Native(_) => Self::Native,
+        }
+    }
+}
+
+/// Objects restrictions can be matched against.
+///
+/// Restrictions that don't apply to an object, e.g. callbacks for atoms, return None.
+trait Matchable: Copy {
+    /// Run a callback restriction against the object.
+    fn callback(self, cb: &CallbackRestrict) -> Option<bool>;
+    /// Run a string restriction against the related object field.
+    fn str_field(self, r: &StrRestrict) -> Option<bool>;
+}
+
+impl Matchable for &atom::Atom {
+    // callbacks only apply to packages
+    fn callback(self, _cb: &CallbackRestrict) -> Option<bool> {
+        None
+    }
+
+    fn str_field(self, r: &StrRestrict) -> Option<bool> {
+        match r.field {
+            StrField::Category => Some(r.is_match(self.category())),
+            StrField::Package => Some(r.is_match(self.package())),
+            StrField::Description => None,
+        }
+    }
+}
+
+impl Matchable for &pkg::Pkg<'_> {
+    fn callback(self, cb: &CallbackRestrict) -> Option<bool> {
+        Some(cb.call(self))
+    }
+
+    fn str_field(self, r: &StrRestrict) -> Option<bool> {
+        match r.field {
+            StrField::Description => self
+                .as_ebuild()
+                .map(|(pkg, _repo)| r.is_match(pkg.description())),
+            _ => self.atom().str_field(r),
+        }
+    }
+}
+
+impl Restrict {
+    /// Match a restriction against an object, returning None if the result depends on
+    /// restrictions that don't apply to it.
+    ///
+    /// Inapplicable restrictions are treated as unknown values so inverting them doesn't cause
+    /// matches, e.g. an inverted callback restriction doesn't match any atoms.
+    fn eval<T: Matchable>(&self, val: T) -> Option<bool>
+    where
+        restrict::Restrict: Restriction<T>,
+    {
+        match self {
+            Self::And(vals) => {
+                let mut result = Some(true);
+                for r in vals {
+                    match r.eval(val) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                result
+            }
+            Self::Or(vals) => {
+                let mut result = Some(false);
+                for r in vals {
+                    match r.eval(val) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => (),
+                    }
+                }
+                result
+            }
+            Self::Not(r) => r.eval(val).map(|m| !m),
+            Self::Callback(cb) => val.callback(cb),
+            Self::Str(r) => val.str_field(r),
+            Self::Native(r) => Some(r.matches(val)),
+        }
+    }
+}
+
+impl<T: Matchable> Restriction<T> for Restrict
+where
+    restrict::Restrict: Restriction<T>,
+{
+    fn matches(&self, val: T) -> bool {
+        self.eval(val).unwrap_or_default()
+    }
+}
+
+impl From<restrict::Restrict> for Restrict {
+    fn from(r: restrict::Restrict) -> Self {
+        Self::Native(r)
+    }
+}
+
+/// Clone an array of restrictions.
+///
+/// # Safety
+/// The argument must be an array of len non-null Restrict pointers or NULL when len is 0.
+unsafe fn restricts(r: *mut *mut Restrict, len: usize) -> Vec<Restrict> {
+    if len == 0 {
+        return vec![];
+    }
+    let restricts = unsafe { slice::from_raw_parts(r, len) };
+    restricts
+        .iter()
+        .map(|r| null_ptr_check!(r.as_ref()).clone())
+        .collect()
+}
 
 /// Parse a restriction string.
 ///
@@ -16,20 +557,350 @@ pub struct Restrict;
 /// # Safety
 /// The argument must be a non-null restriction string.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_restrict_parse_dep(s: *const c_char) -> *mut restrict::Restrict {
+pub unsafe extern "C" fn pkgcraft_restrict_parse_dep(s: *const c_char) -> *mut Restrict {
     let s = null_ptr_check!(s.as_ref());
     let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
     let restrict = unwrap_or_return!(restrict::parse::dep(s), ptr::null_mut());
+    Box::into_raw(Box::new(restrict.into()))
+}
+
+/// Parse a package query string into a restriction.
+///
+/// Queries combine package attribute comparisons using `&&`, `||`, `!`, and parentheses, e.g.
+/// `description =~ "rust" && slot == "0"`. Supported attributes include:
+/// - string attributes such as description and slot, e.g. `slot != "0"`
+/// - EAPI comparisons, e.g. `eapi >= "7"`
+/// - sequence attributes such as homepage, keywords, iuse, and license, e.g.
+///   `keywords any == "~arm64"`
+/// - maintainer attributes, e.g. `maintainers any email == "a@gentoo.org"`
+/// - missing optional attributes, e.g. `maintainers is none`
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null query string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_parse_pkg(s: *const c_char) -> *mut Restrict {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let restrict = unwrap_or_return!(restrict::parse::pkg(s), ptr::null_mut());
+    Box::into_raw(Box::new(restrict.into()))
+}
+
+/// Create a restriction that always matches.
+#[no_mangle]
+pub extern "C" fn pkgcraft_restrict_true() -> *mut Restrict {
+    Box::into_raw(Box::new(Restrict::TRUE))
+}
+
+/// Create a restriction that never matches.
+#[no_mangle]
+pub extern "C" fn pkgcraft_restrict_false() -> *mut Restrict {
+    Box::into_raw(Box::new(Restrict::FALSE))
+}
+
+/// Create a restriction matching if all the given restrictions match.
+///
+/// The given restrictions are copied and must be freed separately. An empty array creates a
+/// restriction that always matches.
+///
+/// # Safety
+/// The argument must be an array of len non-null Restrict pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_and(r: *mut *mut Restrict, len: usize) -> *mut Restrict {
+    let restricts = unsafe { restricts(r, len) };
+    Box::into_raw(Box::new(Restrict::and(restricts)))
+}
+
+/// Create a restriction matching if any of the given restrictions match.
+///
+/// The given restrictions are copied and must be freed separately. An empty array creates a
+/// restriction that never matches.
+///
+/// # Safety
+/// The argument must be an array of len non-null Restrict pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_or(r: *mut *mut Restrict, len: usize) -> *mut Restrict {
+    let restricts = unsafe { restricts(r, len) };
+    Box::into_raw(Box::new(Restrict::or(restricts)))
+}
+
+/// Create a restriction inverting the given restriction.
+///
+/// The given restriction is copied and must be freed separately.
+///
+/// # Safety
+/// The argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_not(r: *mut Restrict) -> *mut Restrict {
+    let restrict = null_ptr_check!(r.as_ref());
+    Box::into_raw(Box::new(Restrict::not(restrict.clone())))
+}
+
+/// Create a restriction matching packages using a callback.
+///
+/// The callback is passed each package along with the given userdata which must remain valid
+/// while the restriction or any restriction containing it is in use. Callback restrictions never
+/// match atoms or CPV strings, even when inverted, so restrictions depending on them don't match
+/// either.
+///
+/// Returns NULL on error.
+#[no_mangle]
+pub extern "C" fn pkgcraft_restrict_pkg_callback(
+    func: Option<PkgCallback>,
+    data: *mut c_void,
+) -> *mut Restrict {
+    let func = unwrap_or_return!(
+        func.ok_or_else(|| Error::new("missing callback function")),
+        ptr::null_mut()
+    );
+    Box::into_raw(Box::new(Restrict::Callback(CallbackRestrict {
+        func,
+        data,
+    })))
+}
+
+/// Create a string restriction.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null string.
+unsafe fn str_restrict<F>(s: *const c_char, field: StrField, func: F) -> *mut Restrict
+where
+    F: Fn(StrField, &str) -> crate::Result<StrRestrict>,
+{
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let restrict = unwrap_or_return!(func(field, s), ptr::null_mut());
+    Box::into_raw(Box::new(Restrict::Str(restrict)))
+}
+
+/// Create a restriction matching package categories using a glob pattern.
+///
+/// Glob patterns support '*' to match any number of characters and '?' to match a single
+/// character, e.g. "dev-*".
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null glob string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_category_glob(s: *const c_char) -> *mut Restrict {
+    unsafe { str_restrict(s, StrField::Category, StrRestrict::glob) }
+}
+
+/// Create a restriction matching package names using a glob pattern, e.g. "*-utils".
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null glob string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_package_glob(s: *const c_char) -> *mut Restrict {
+    unsafe { str_restrict(s, StrField::Package, StrRestrict::glob) }
+}
+
+/// Create a restriction matching package category and name globs, e.g. "dev-*/*-utils".
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null glob string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_glob(s: *const c_char) -> *mut Restrict {
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), ptr::null_mut()) };
+    let restrict = unwrap_or_return!(Restrict::glob(s), ptr::null_mut());
     Box::into_raw(Box::new(restrict))
 }
 
+/// Create a restriction matching package categories using a regular expression.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null regex string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_category_regex(s: *const c_char) -> *mut Restrict {
+    unsafe { str_restrict(s, StrField::Category, StrRestrict::regex) }
+}
+
+/// Create a restriction matching package names using a regular expression.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null regex string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_package_regex(s: *const c_char) -> *mut Restrict {
+    unsafe { str_restrict(s, StrField::Package, StrRestrict::regex) }
+}
+
+/// Create a restriction matching package descriptions using a regular expression.
+///
+/// Description restrictions never match atoms or CPV strings, even when inverted.
+///
+/// Returns NULL on error.
+///
+/// # Safety
+/// The argument must be a non-null regex string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_description_regex(s: *const c_char) -> *mut Restrict {
+    unsafe { str_restrict(s, StrField::Description, StrRestrict::regex) }
+}
+
+/// Determine if a restriction matches an atom.
+///
+/// # Safety
+/// The arguments must be non-null Restrict and Atom pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_matches_atom(
+    r: *mut Restrict,
+    a: *mut atom::Atom,
+) -> bool {
+    let restrict = null_ptr_check!(r.as_ref());
+    let atom = null_ptr_check!(a.as_ref());
+    restrict.matches(atom)
+}
+
+/// Determine if a restriction matches a package.
+///
+/// # Safety
+/// The arguments must be non-null Restrict and Pkg pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_matches_pkg(r: *mut Restrict, p: *mut pkg::Pkg) -> bool {
+    let restrict = null_ptr_check!(r.as_ref());
+    let pkg = null_ptr_check!(p.as_ref());
+    restrict.matches(pkg)
+}
+
+/// Determine if a restriction matches a CPV string, e.g. "cat/pkg-1-r2".
+///
+/// Returns false on error.
+///
+/// # Safety
+/// The arguments must be a non-null Restrict pointer and a non-null CPV string.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_matches_cpv_str(
+    r: *mut Restrict,
+    s: *const c_char,
+) -> bool {
+    let restrict = null_ptr_check!(r.as_ref());
+    let s = null_ptr_check!(s.as_ref());
+    let s = unsafe { unwrap_or_return!(CStr::from_ptr(s).to_str(), false) };
+    let cpv = unwrap_or_return!(atom::cpv(s), false);
+    restrict.matches(&cpv)
+}
+
+/// Return the kind of a given restriction.
+///
+/// # Safety
+/// The argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_kind(r: *mut Restrict) -> RestrictKind {
+    let restrict = null_ptr_check!(r.as_ref());
+    restrict.into()
+}
+
+/// Return the child restrictions for a given and, or, or not restriction.
+///
+/// Returns NULL for all other restriction kinds.
+///
+/// # Safety
+/// The restrict argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_children(
+    r: *mut Restrict,
+    len: *mut usize,
+) -> *mut *mut Restrict {
+    let restrict = null_ptr_check!(r.as_ref());
+    match RestrictKind::from(restrict) {
+        RestrictKind::And | RestrictKind::Or | RestrictKind::Not => {
+            let mut ptrs: Vec<_> = restrict
+                .children()
+                .into_iter()
+                .map(|r| Box::into_raw(Box::new(r)))
+                .collect();
+            ptrs.shrink_to_fit();
+            unsafe { *len = ptrs.len() };
+            let p = ptrs.as_mut_ptr();
+            mem::forget(ptrs);
+            p
+        }
+        _ => {
+            unsafe { *len = 0 };
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Return a simplified version of a given restriction.
+///
+/// Nested and/or restrictions are flattened, duplicate and constant terms are removed, and
+/// double negations are dropped.
+///
+/// # Safety
+/// The argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_simplify(r: *mut Restrict) -> *mut Restrict {
+    let restrict = null_ptr_check!(r.as_ref());
+    Box::into_raw(Box::new(restrict.simplify()))
+}
+
+/// Return the string for a given restriction.
+///
+/// # Safety
+/// The argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_str(r: *mut Restrict) -> *mut c_char {
+    let restrict = null_ptr_check!(r.as_ref());
+    CString::new(restrict.to_string()).unwrap().into_raw()
+}
+
+/// Determine if two restrictions are equal.
+///
+/// # Safety
+/// The arguments must be non-null Restrict pointers.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_eq(r1: *mut Restrict, r2: *mut Restrict) -> bool {
+    let r1 = null_ptr_check!(r1.as_ref());
+    let r2 = null_ptr_check!(r2.as_ref());
+    r1 == r2
+}
+
+/// Return the hash value for a given restriction.
+///
+/// # Safety
+/// The argument must be a non-null Restrict pointer.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restrict_hash(r: *mut Restrict) -> u64 {
+    let restrict = null_ptr_check!(r.as_ref());
+    hash(restrict)
+}
+
 /// Free a restriction.
 ///
 /// # Safety
 /// The argument must be a Restrict pointer or NULL.
 #[no_mangle]
-pub unsafe extern "C" fn pkgcraft_restrict_free(r: *mut restrict::Restrict) {
+pub unsafe extern "C" fn pkgcraft_restrict_free(r: *mut Restrict) {
     if !r.is_null() {
         unsafe { drop(Box::from_raw(r)) };
     }
 }
+
+/// Free an array of restrictions.
+///
+/// # Safety
+/// The argument must be an array of Restrict pointers or NULL along with the length of the array.
+#[no_mangle]
+pub unsafe extern "C" fn pkgcraft_restricts_free(r: *mut *mut Restrict, len: usize) {
+    if !r.is_null() {
+        unsafe {
+            for r in Vec::from_raw_parts(r, len, len).into_iter() {
+                pkgcraft_restrict_free(r);
+            }
+        }
+    }
+}
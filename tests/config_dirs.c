@@ -0,0 +1,90 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Assert a config directory matches the expected path, NULL meaning it can't be determined.
+static void check_dir(char *dir, const char *expected) {
+	if (expected == NULL) {
+		assert(dir == NULL);
+		assert(pkgcraft_last_error() != NULL);
+	} else {
+		assert(dir != NULL);
+		assert(strcmp(dir, expected) == 0);
+		pkgcraft_str_free(dir);
+	}
+}
+
+int main (int argc, char **argv) {
+	char *kind, *dir, config_dir[4096], cache_dir[4096], data_dir[4096];
+	ConfigOptions options = {0};
+	Config *config;
+
+	if (argc == 2) {
+		kind = argv[1];
+	} else {
+		fprintf(stderr, "missing required config dirs kind arg\n");
+		exit(1);
+	}
+
+	dir = temp_dir();
+	if (strcmp(kind, "prefix") == 0) {
+		// directories default to paths under the prefix
+		options.prefix = dir;
+		config = pkgcraft_config_new(&options);
+		assert(config != NULL);
+		snprintf(config_dir, sizeof(config_dir), "%s/etc/pkgcraft", dir);
+		snprintf(cache_dir, sizeof(cache_dir), "%s/var/cache/pkgcraft", dir);
+		snprintf(data_dir, sizeof(data_dir), "%s/var/lib/pkgcraft", dir);
+		check_dir(pkgcraft_config_dir(config), config_dir);
+		check_dir(pkgcraft_config_cache_dir(config), cache_dir);
+		check_dir(pkgcraft_config_data_dir(config), data_dir);
+	} else if (strcmp(kind, "explicit") == 0) {
+		// explicit directories override the defaults
+		snprintf(config_dir, sizeof(config_dir), "%s/config", dir);
+		snprintf(cache_dir, sizeof(cache_dir), "%s/cache", dir);
+		snprintf(data_dir, sizeof(data_dir), "%s/data", dir);
+		options.config_dir = config_dir;
+		options.cache_dir = cache_dir;
+		options.data_dir = data_dir;
+		options.prefix = dir;
+		config = pkgcraft_config_new(&options);
+		assert(config != NULL);
+		check_dir(pkgcraft_config_dir(config), config_dir);
+		check_dir(pkgcraft_config_cache_dir(config), cache_dir);
+		check_dir(pkgcraft_config_data_dir(config), data_dir);
+	} else if (strcmp(kind, "xdg") == 0) {
+		// XDG user directories are used without a prefix
+		setenv("XDG_CONFIG_HOME", dir, 1);
+		unsetenv("XDG_CACHE_HOME");
+		unsetenv("XDG_DATA_HOME");
+		setenv("HOME", dir, 1);
+		config = pkgcraft_config();
+		assert(config != NULL);
+		snprintf(config_dir, sizeof(config_dir), "%s/pkgcraft", dir);
+		snprintf(cache_dir, sizeof(cache_dir), "%s/.cache/pkgcraft", dir);
+		snprintf(data_dir, sizeof(data_dir), "%s/.local/share/pkgcraft", dir);
+		check_dir(pkgcraft_config_dir(config), config_dir);
+		check_dir(pkgcraft_config_cache_dir(config), cache_dir);
+		check_dir(pkgcraft_config_data_dir(config), data_dir);
+	} else if (strcmp(kind, "no_home") == 0) {
+		// configs are still created when user directories can't be determined
+		unsetenv("HOME");
+		unsetenv("XDG_CONFIG_HOME");
+		unsetenv("XDG_CACHE_HOME");
+		unsetenv("XDG_DATA_HOME");
+		config = pkgcraft_config();
+		assert(config != NULL);
+		check_dir(pkgcraft_config_dir(config), NULL);
+		check_dir(pkgcraft_config_cache_dir(config), NULL);
+		check_dir(pkgcraft_config_data_dir(config), NULL);
+	} else {
+		fprintf(stderr, "unknown config dirs kind: %s\n", kind);
+		exit(1);
+	}
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
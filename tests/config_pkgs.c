@@ -0,0 +1,77 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Add an ebuild repo containing the given cpvs to a config.
+static void add_repo(Config *config, const char *dir, const char *id, int priority,
+		const char **cpvs, size_t len) {
+	char path[4096], file[4096], cat[256], pkg[256], ver[256];
+	snprintf(path, sizeof(path), "%s/%s", dir, id);
+	ebuild_repo(path, id);
+	for (size_t i = 0; i < len; i++) {
+		assert(sscanf(cpvs[i], "%255[^/]/%255[^-]-%255s", cat, pkg, ver) == 3);
+		snprintf(file, sizeof(file), "%s/%s/%s-%s.ebuild", cat, pkg, pkg, ver);
+		write_file(path, file, "EAPI=8\nSLOT=0\n");
+	}
+	RepoConfig *r = pkgcraft_config_add_repo_path(config, id, priority, path);
+	assert(r != NULL);
+	pkgcraft_repo_config_free(r);
+}
+
+int main (int argc, char **argv) {
+	ConfigOptions options = {0};
+	Config *config;
+	ConfigPkgIter *iter;
+	Restrict *r = NULL;
+	Pkg *pkg;
+	char pkg_str[512];
+	size_t count = 0;
+
+	if (argc < 3) {
+		fprintf(stderr, "usage: config_pkgs restrict skip_shadowed [repo:cpv ...]\n");
+		exit(1);
+	}
+
+	// an empty restrict arg iterates over all packages
+	if (strlen(argv[1]) > 0) {
+		r = pkgcraft_restrict_parse_dep(argv[1]);
+		assert(r != NULL);
+	}
+	bool skip_shadowed = strcmp(argv[2], "1") == 0;
+	char **expected = &argv[3];
+	size_t expected_len = argc - 3;
+
+	// both repos contain cat/a-1 while the lower priority repo is added first
+	char *dir = temp_dir();
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+	add_repo(config, dir, "low", 1, (const char *[]){"cat/a-1", "cat/a-2", "cat/c-1"}, 3);
+	add_repo(config, dir, "high", 2, (const char *[]){"cat/a-1", "cat/b-1"}, 2);
+
+	iter = pkgcraft_config_pkg_iter(config, r, skip_shadowed);
+	assert(iter != NULL);
+	while ((pkg = pkgcraft_config_pkg_iter_next(iter))) {
+		char *id = pkgcraft_repo_id((Repo *)pkgcraft_pkg_repo(pkg));
+		char *cpv = pkgcraft_atom_cpv((Atom *)pkgcraft_pkg_atom(pkg));
+		snprintf(pkg_str, sizeof(pkg_str), "%s:%s", id, cpv);
+		assert(count < expected_len);
+		if (strcmp(pkg_str, expected[count]) != 0) {
+			fprintf(stderr, "expected %s, got %s\n", expected[count], pkg_str);
+			exit(1);
+		}
+		count++;
+		pkgcraft_str_free(id);
+		pkgcraft_str_free(cpv);
+		pkgcraft_pkg_free(pkg);
+	}
+	assert(count == expected_len);
+
+	pkgcraft_config_pkg_iter_free(iter);
+	pkgcraft_restrict_free(r);
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
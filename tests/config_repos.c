@@ -0,0 +1,70 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Assert a config's repos are in the expected order, agreeing with their repo comparisons.
+static void check_repos(Config *config, const char **expected, size_t expected_len) {
+	size_t len;
+	RepoConfig **repos = pkgcraft_config_repos(config, &len);
+	assert(len == expected_len);
+	for (size_t i = 0; i < len; i++) {
+		assert(strcmp(repos[i]->id, expected[i]) == 0);
+		if (i > 0) {
+			assert(pkgcraft_repo_cmp(repos[i - 1]->repo, repos[i]->repo) == -1);
+		}
+	}
+	pkgcraft_repos_free(repos, len);
+}
+
+// Add an ebuild repo to a config.
+static void add_repo(Config *config, const char *dir, const char *id, int priority) {
+	char path[4096];
+	snprintf(path, sizeof(path), "%s/%s", dir, id);
+	ebuild_repo(path, id);
+	RepoConfig *r = pkgcraft_config_add_repo_path(config, id, priority, path);
+	assert(r != NULL);
+	assert(r->priority == priority);
+	pkgcraft_repo_config_free(r);
+}
+
+int main (int argc, char **argv) {
+	ConfigOptions options = {0};
+	Config *config;
+	RepoConfig *r;
+	char *dir = temp_dir();
+
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+
+	add_repo(config, dir, "a", 1);
+	add_repo(config, dir, "b", 2);
+	add_repo(config, dir, "c", 3);
+	check_repos(config, (const char *[]){"c", "b", "a"}, 3);
+
+	// changing priorities reorders repos and updates the repos themselves
+	assert(pkgcraft_config_repo_set_priority(config, "a", 5));
+	check_repos(config, (const char *[]){"a", "c", "b"}, 3);
+	r = pkgcraft_config_repo_get(config, "a");
+	assert(r != NULL);
+	assert(r->priority == 5);
+	pkgcraft_repo_config_free(r);
+
+	// nonexistent repos can't be changed
+	assert(!pkgcraft_config_repo_set_priority(config, "z", 1));
+	assert(pkgcraft_last_error() != NULL);
+
+	// removed repos are fully unregistered, allowing them to be added again
+	assert(pkgcraft_config_repo_remove(config, "c"));
+	check_repos(config, (const char *[]){"a", "b"}, 2);
+	assert(pkgcraft_config_repo_get(config, "c") == NULL);
+	assert(!pkgcraft_config_repo_remove(config, "c"));
+	add_repo(config, dir, "c", 0);
+	check_repos(config, (const char *[]){"a", "b", "c"}, 3);
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
@@ -0,0 +1,122 @@
+#include "utils.h"
+
+#include <unistd.h>
+
+#include <pkgcraft.h>
+
+// Track progress messages, asserting they're reported for the expected repo.
+static void progress(const char *id, const char *msg, void *data) {
+	assert(strcmp(id, "sync") == 0);
+	size_t *count = data;
+	*count += 1;
+}
+
+// Run a git command in a given repo, asserting it succeeds.
+static void git(const char *repo, const char *args) {
+	char cmd[8192];
+	snprintf(cmd, sizeof(cmd),
+		"git -C %s -c user.name=test -c user.email=test@example.com %s", repo, args);
+	assert(system(cmd) == 0);
+}
+
+// Sync the repo, asserting the synced content and that it's still registered once.
+static void sync_repo(Config *config, const char *location, const char *file) {
+	size_t count = 0, len;
+	char path[4096];
+
+	assert(pkgcraft_config_sync(config, "sync", progress, &count));
+	assert(count > 0);
+	snprintf(path, sizeof(path), "%s/%s", location, file);
+	assert(access(path, F_OK) == 0);
+
+	RepoConfig **repos = pkgcraft_config_repos(config, &len);
+	assert(len == 1);
+	assert(strcmp(repos[0]->id, "sync") == 0);
+	assert(strcmp(repos[0]->location, location) == 0);
+	pkgcraft_repos_free(repos, len);
+}
+
+int main (int argc, char **argv) {
+	ConfigOptions options = {0};
+	Config *config;
+	RepoConfig *r;
+	const char *kind, *sync_type;
+	char src[4096], location[4096], uri[4096], cmd[8192];
+
+	if (argc != 2) {
+		fprintf(stderr, "usage: config_sync git|tar|tar_flat|rsync\n");
+		exit(1);
+	}
+	kind = argv[1];
+	sync_type = strcmp(kind, "tar_flat") == 0 ? "tar" : kind;
+
+	char *dir = temp_dir();
+	snprintf(src, sizeof(src), "%s/src", dir);
+	snprintf(location, sizeof(location), "%s/repo", dir);
+	ebuild_repo(src, "sync");
+
+	if (strcmp(kind, "git") == 0) {
+		git(src, "init -q");
+		git(src, "add -A");
+		git(src, "commit -qm init");
+		// checkouts are updated from the sync uri instead of their origin
+		snprintf(cmd, sizeof(cmd), "git clone -q %s %s/mirror && git clone -q %s/mirror %s",
+			src, dir, dir, location);
+		assert(system(cmd) == 0);
+		strcpy(uri, src);
+	} else if (strcmp(sync_type, "tar") == 0) {
+		ebuild_repo(location, "sync");
+		snprintf(uri, sizeof(uri), "%s/snapshot.tar.gz", dir);
+	} else if (strcmp(kind, "rsync") == 0) {
+		ebuild_repo(location, "sync");
+		// files missing from the source are removed
+		write_file(location, "cat/stale/stale-1.ebuild", "EAPI=8\n");
+		strcpy(uri, src);
+	} else {
+		fprintf(stderr, "unknown sync type: %s\n", kind);
+		exit(1);
+	}
+
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+	r = pkgcraft_config_add_repo_path(config, "sync", 0, location);
+	assert(r != NULL);
+	pkgcraft_repo_config_free(r);
+	assert(pkgcraft_config_repo_set_sync(config, "sync", sync_type, uri));
+
+	// repeated syncs update the already registered repo
+	for (int i = 0; i < 2; i++) {
+		char file[256];
+		snprintf(file, sizeof(file), "cat/pkg%d/pkg%d-1.ebuild", i, i);
+		write_file(src, file, "EAPI=8\n");
+		if (strcmp(kind, "git") == 0) {
+			git(src, "add -A");
+			git(src, "commit -qm update");
+		} else if (strcmp(kind, "tar") == 0) {
+			// tarballs with a single top-level directory use it as the repo root
+			snprintf(cmd, sizeof(cmd), "tar -czf %s -C %s src", uri, dir);
+			assert(system(cmd) == 0);
+		} else if (strcmp(kind, "tar_flat") == 0) {
+			snprintf(cmd, sizeof(cmd), "tar -czf %s -C %s .", uri, src);
+			assert(system(cmd) == 0);
+		}
+		sync_repo(config, location, file);
+	}
+	if (strcmp(kind, "rsync") == 0) {
+		snprintf(cmd, sizeof(cmd), "%s/cat/stale", location);
+		assert(access(cmd, F_OK) != 0);
+	}
+
+	// syncing fails for unknown repos and repos lacking a sync uri
+	assert(!pkgcraft_config_sync(config, "nonexistent", NULL, NULL));
+	assert(pkgcraft_config_repo_set_sync(config, "sync", NULL, NULL));
+	assert(!pkgcraft_config_sync(config, "sync", NULL, NULL));
+	assert(pkgcraft_last_error() != NULL);
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
@@ -0,0 +1,49 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *deps, *expected;
+	char *value;
+	size_t length, atoms = 0;
+	DepSet *d = NULL;
+	DepSetIter *iter = NULL;
+	DepRestrict *r = NULL;
+
+	if (argc == 2) {
+		deps = argv[1];
+	} else if (argc < 2) {
+		fprintf(stderr, "missing required dependencies arg\n");
+		exit(1);
+	}
+
+	d = pkgcraft_depset_dependencies(deps, NULL);
+	assert(d != NULL);
+
+	value = pkgcraft_depset_str(d);
+	expected = getenv("str");
+	assert(strcmp(value, expected ? expected : deps) == 0);
+	pkgcraft_str_free(value);
+
+	DepRestrict **restricts = pkgcraft_depset_restricts(d, &length);
+	assert(length == (size_t)atoi(getenv("restricts")));
+	pkgcraft_dep_restricts_free(restricts, length);
+
+	iter = pkgcraft_depset_iter(d);
+	while ((r = pkgcraft_depset_iter_next(iter))) {
+		if (pkgcraft_dep_restrict_kind(r) == Matches) {
+			assert(pkgcraft_dep_restrict_atom(r) != NULL);
+			atoms++;
+		}
+		pkgcraft_dep_restrict_free(r);
+	}
+	assert(atoms == (size_t)atoi(getenv("atoms")));
+
+	pkgcraft_depset_iter_free(iter);
+	pkgcraft_depset_free(d);
+
+	return 0;
+}
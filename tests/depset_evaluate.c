@@ -0,0 +1,67 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *deps, *flags_env, *flag, *atoms_env, *expected, *value;
+	char *flags[16];
+	size_t flags_len = 0, length, i = 0;
+	bool collapse = getenv("collapse") != NULL;
+	DepSet *d = NULL, *evaluated = NULL;
+	Atom **atoms = NULL;
+
+	if (argc == 2) {
+		deps = argv[1];
+	} else if (argc < 2) {
+		fprintf(stderr, "missing required dependencies arg\n");
+		exit(1);
+	}
+
+	flags_env = getenv("flags");
+	if (flags_env) {
+		flag = strtok(flags_env, ",");
+		while (flag && flags_len < 16) {
+			flags[flags_len++] = flag;
+			flag = strtok(NULL, ",");
+		}
+	}
+
+	d = pkgcraft_depset_dependencies(deps, NULL);
+	assert(d != NULL);
+
+	evaluated = pkgcraft_depset_evaluate(d, (const char **)flags, flags_len, collapse);
+	assert(evaluated != NULL);
+	assert(pkgcraft_depset_kind(evaluated) == DepSetKind_Dependencies);
+
+	value = pkgcraft_depset_str(evaluated);
+	assert(strcmp(value, getenv("str")) == 0);
+	pkgcraft_str_free(value);
+
+	// atoms from the evaluated DepSet are returned in order
+	atoms = pkgcraft_depset_atoms(evaluated, &length);
+	assert(atoms != NULL);
+	atoms_env = getenv("atoms");
+	expected = strtok(atoms_env, ",");
+	while (expected) {
+		assert(i < length);
+		value = pkgcraft_atom_str(atoms[i++]);
+		assert(strcmp(value, expected) == 0);
+		pkgcraft_str_free(value);
+		expected = strtok(NULL, ",");
+	}
+	assert(i == length);
+	pkgcraft_atoms_free(atoms, length);
+
+	// non-dependency DepSets are rejected
+	pkgcraft_depset_free(evaluated);
+	evaluated = pkgcraft_depset_license("MIT");
+	assert(pkgcraft_depset_atoms(evaluated, &length) == NULL);
+
+	pkgcraft_depset_free(evaluated);
+	pkgcraft_depset_free(d);
+
+	return 0;
+}
@@ -0,0 +1,60 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *kind, *value, *expected, *str;
+	size_t length;
+	DepSet *d = NULL;
+	DepSetKind expected_kind;
+
+	if (argc == 3) {
+		kind = argv[1];
+		value = argv[2];
+	} else if (argc < 3) {
+		fprintf(stderr, "missing required DepSet kind and value args\n");
+		exit(1);
+	}
+
+	if (strcmp(kind, "license") == 0) {
+		d = pkgcraft_depset_license(value);
+		expected_kind = DepSetKind_License;
+	} else if (strcmp(kind, "properties") == 0) {
+		d = pkgcraft_depset_properties(value);
+		expected_kind = DepSetKind_Properties;
+	} else if (strcmp(kind, "required_use") == 0) {
+		d = pkgcraft_depset_required_use(value, getenv("eapi"));
+		expected_kind = DepSetKind_RequiredUse;
+	} else if (strcmp(kind, "restrict") == 0) {
+		d = pkgcraft_depset_restrict(value);
+		expected_kind = DepSetKind_Restrict;
+	} else {
+		fprintf(stderr, "unknown DepSet kind: %s\n", kind);
+		exit(1);
+	}
+
+	// invalid values fail to parse
+	if (!getenv("restricts")) {
+		assert(d == NULL);
+		return 0;
+	}
+
+	assert(d != NULL);
+	assert(pkgcraft_depset_kind(d) == expected_kind);
+
+	str = pkgcraft_depset_str(d);
+	expected = getenv("str");
+	assert(strcmp(str, expected ? expected : value) == 0);
+	pkgcraft_str_free(str);
+
+	DepRestrict **restricts = pkgcraft_depset_restricts(d, &length);
+	assert(length == (size_t)atoi(getenv("restricts")));
+	pkgcraft_dep_restricts_free(restricts, length);
+
+	pkgcraft_depset_free(d);
+
+	return 0;
+}
@@ -0,0 +1,44 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *content, *expected, *id;
+	const Eapi *eapi, **eapis;
+	size_t len;
+
+	if (argc == 2) {
+		content = argv[1];
+	} else if (argc < 2) {
+		fprintf(stderr, "missing required ebuild content arg\n");
+		exit(1);
+	}
+
+	eapi = pkgcraft_eapi_from_str(content);
+	expected = getenv("eapi");
+	if (!expected) {
+		assert(eapi == NULL);
+		return 0;
+	}
+
+	id = pkgcraft_eapi_as_str(eapi);
+	assert(strcmp(id, expected) == 0);
+
+	// the EAPI is the lowest in its range of newer EAPIs
+	char range[64];
+	snprintf(range, sizeof(range), ">=%s", id);
+	eapis = pkgcraft_eapis_range(range, &len);
+	assert(len > 0);
+	assert(pkgcraft_eapi_cmp(eapis[0], eapi) == 0);
+	for (size_t i = 1; i < len; i++) {
+		assert(pkgcraft_eapi_cmp(eapis[i], eapi) == 1);
+	}
+
+	pkgcraft_eapis_free(eapis, len);
+	pkgcraft_str_free(id);
+
+	return 0;
+}
@@ -0,0 +1,45 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	const Eapi **eapis;
+	char **names, *name;
+	EapiFeature *features;
+	size_t eapis_len, names_len, features_len, supported;
+
+	eapis = pkgcraft_eapis(&eapis_len);
+	names = pkgcraft_eapi_feature_names(&names_len);
+	assert(names_len > 0);
+
+	// feature variants and names agree with pkgcraft for every EAPI
+	for (size_t i = 0; i < eapis_len; i++) {
+		supported = 0;
+		for (size_t j = 0; j < names_len; j++) {
+			name = pkgcraft_eapi_feature_str((EapiFeature)j);
+			assert(strcmp(name, names[j]) == 0);
+			bool has = pkgcraft_eapi_has_feature(eapis[i], (EapiFeature)j);
+			assert(pkgcraft_eapi_has(eapis[i], name) == has);
+			supported += has;
+			pkgcraft_str_free(name);
+		}
+
+		features = pkgcraft_eapi_features(eapis[i], &features_len);
+		assert(features_len == supported);
+		for (size_t j = 0; j < features_len; j++) {
+			assert(pkgcraft_eapi_has_feature(eapis[i], features[j]));
+		}
+		pkgcraft_eapi_features_free(features, features_len);
+	}
+
+	// unknown features are never supported
+	assert(!pkgcraft_eapi_has(eapis[eapis_len - 1], "unknown_feature"));
+
+	pkgcraft_str_array_free(names, names_len);
+	pkgcraft_eapis_free(eapis, eapis_len);
+
+	return 0;
+}
@@ -0,0 +1,76 @@
+#include <assert.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+static bool contains(char **values, size_t len, char *value) {
+	for (size_t i = 0; i < len; i++) {
+		if (strcmp(values[i], value) == 0) {
+			return true;
+		}
+	}
+	return false;
+}
+
+int main (int argc, char **argv) {
+	char *kind, *missing, *value, **values;
+	const Eapi *eapi;
+	size_t len;
+
+	if (argc < 3) {
+		fprintf(stderr, "missing required metadata kind and EAPI args\n");
+		exit(1);
+	}
+	kind = argv[1];
+	eapi = pkgcraft_get_eapi(argv[2]);
+	assert(eapi != NULL);
+
+	if (strcmp(kind, "phases") == 0) {
+		values = pkgcraft_eapi_phases(eapi, &len);
+	} else if (strcmp(kind, "mandatory_keys") == 0) {
+		values = pkgcraft_eapi_mandatory_keys(eapi, &len);
+	} else if (strcmp(kind, "optional_keys") == 0) {
+		values = pkgcraft_eapi_optional_keys(eapi, &len);
+	} else if (strcmp(kind, "dep_keys") == 0) {
+		values = pkgcraft_eapi_dep_keys(eapi, &len);
+	} else if (strcmp(kind, "incremental_keys") == 0) {
+		values = pkgcraft_eapi_incremental_keys(eapi, &len);
+	} else if (strcmp(kind, "archives") == 0) {
+		values = pkgcraft_eapi_archives(eapi, &len);
+	} else if (strcmp(kind, "banned_commands") == 0) {
+		values = pkgcraft_eapi_banned_commands(eapi, &len);
+	} else if (strcmp(kind, "deprecated_commands") == 0) {
+		values = pkgcraft_eapi_deprecated_commands(eapi, &len);
+	} else {
+		fprintf(stderr, "unknown metadata kind: %s\n", kind);
+		exit(1);
+	}
+	assert(values != NULL);
+
+	// values are sorted by name
+	for (size_t i = 1; i < len; i++) {
+		assert(strcmp(values[i - 1], values[i]) < 0);
+	}
+
+	// remaining args are expected values
+	for (int i = 3; i < argc; i++) {
+		assert(contains(values, len, argv[i]));
+	}
+
+	// values that must be absent are passed via a space-separated env var
+	missing = getenv("missing");
+	if (missing) {
+		missing = strdup(missing);
+		for (value = strtok(missing, " "); value; value = strtok(NULL, " ")) {
+			assert(!contains(values, len, value));
+		}
+		free(missing);
+	}
+
+	pkgcraft_str_array_free(values, len);
+
+	return 0;
+}
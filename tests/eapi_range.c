@@ -0,0 +1,43 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *range, *expected, *id, *ids;
+	const Eapi **eapis;
+	size_t len;
+
+	if (argc == 2) {
+		range = argv[1];
+	} else {
+		fprintf(stderr, "missing required EAPI range arg\n");
+		exit(1);
+	}
+
+	eapis = pkgcraft_eapis_range(range, &len);
+	expected = getenv("eapis");
+	if (!expected) {
+		assert(eapis == NULL);
+		return 0;
+	}
+
+	// join the EAPI ids using spaces for comparison
+	ids = calloc(1, 256);
+	for (size_t i = 0; i < len; i++) {
+		id = pkgcraft_eapi_as_str(eapis[i]);
+		if (i > 0) {
+			strcat(ids, " ");
+		}
+		strcat(ids, id);
+		pkgcraft_str_free(id);
+	}
+	assert(strcmp(ids, expected) == 0);
+
+	free(ids);
+	pkgcraft_eapis_free(eapis, len);
+
+	return 0;
+}
@@ -0,0 +1,82 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+// parse a depset, returning its validity
+static bool depset(DepSet *d) {
+	if (d == NULL) {
+		return false;
+	}
+	pkgcraft_depset_free(d);
+	return true;
+}
+
+int main (int argc, char **argv) {
+	char *kind, *value, *eapi_str;
+	const Eapi *eapi = NULL;
+	bool expected, valid;
+
+	if (argc == 4) {
+		kind = argv[1];
+		value = argv[2];
+		expected = atoi(argv[3]);
+	} else if (argc < 4) {
+		fprintf(stderr, "missing required parse args\n");
+		exit(1);
+	}
+
+	// EAPI-dependent parsing must give the same results for EAPI strings and handles
+	eapi_str = getenv("eapi");
+	if (eapi_str) {
+		eapi = pkgcraft_get_eapi(eapi_str);
+		assert(eapi != NULL);
+	}
+
+	if (strcmp(kind, "use_flag") == 0) {
+		valid = pkgcraft_parse_use_flag(value);
+	} else if (strcmp(kind, "keyword") == 0) {
+		valid = pkgcraft_parse_keyword(value);
+	} else if (strcmp(kind, "license") == 0) {
+		valid = pkgcraft_parse_license(value);
+	} else if (strcmp(kind, "eclass") == 0) {
+		valid = pkgcraft_parse_eclass(value);
+	} else if (strcmp(kind, "iuse") == 0) {
+		valid = pkgcraft_parse_iuse(value, eapi_str);
+		assert(pkgcraft_parse_iuse_with_eapi(value, eapi) == valid);
+	} else if (strcmp(kind, "slot") == 0) {
+		valid = pkgcraft_parse_slot(value, eapi_str);
+		assert(pkgcraft_parse_slot_with_eapi(value, eapi) == valid);
+	} else if (strcmp(kind, "atom") == 0) {
+		valid = pkgcraft_parse_atom(value, eapi_str);
+		assert(pkgcraft_parse_atom_with_eapi(value, eapi) == valid);
+		Atom *atom = pkgcraft_atom_with_eapi(value, eapi);
+		assert((atom != NULL) == valid);
+		pkgcraft_atom_free(atom);
+	} else if (strcmp(kind, "dependencies") == 0) {
+		valid = depset(pkgcraft_depset_dependencies(value, eapi_str));
+		assert(depset(pkgcraft_depset_dependencies_with_eapi(value, eapi)) == valid);
+	} else if (strcmp(kind, "required_use") == 0) {
+		valid = depset(pkgcraft_depset_required_use(value, eapi_str));
+		assert(depset(pkgcraft_depset_required_use_with_eapi(value, eapi)) == valid);
+	} else if (strcmp(kind, "src_uri") == 0) {
+		valid = depset(pkgcraft_depset_src_uri(value, eapi_str));
+		assert(depset(pkgcraft_depset_src_uri_with_eapi(value, eapi)) == valid);
+	} else {
+		fprintf(stderr, "unknown parse kind: %s\n", kind);
+		exit(1);
+	}
+
+	assert(valid == expected);
+
+	// failures set an error message
+	if (!valid) {
+		char *error = pkgcraft_last_error();
+		assert(error != NULL);
+		pkgcraft_str_free(error);
+	}
+
+	return 0;
+}
@@ -0,0 +1,130 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Assert a make.conf variable has the expected value, NULL meaning it's unset.
+static void check_var(Config *config, const char *name, const char *expected) {
+	char *value = pkgcraft_config_make_conf_var(config, name);
+	if (expected == NULL) {
+		assert(value == NULL);
+	} else {
+		assert(value != NULL);
+		assert(strcmp(value, expected) == 0);
+		pkgcraft_str_free(value);
+	}
+}
+
+int main (int argc, char **argv) {
+	ConfigOptions options = {0};
+	Config *config;
+	Repo *r;
+	PkgIter *iter;
+	Pkg *pkg;
+	char **values;
+	size_t len;
+
+	if (argc < 2) {
+		fprintf(stderr, "usage: portage_config kind [cpv expected...]\n");
+		exit(1);
+	}
+
+	const char *kind = argv[1];
+	char *dir = temp_dir();
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+
+	if (strcmp(kind, "make_conf") == 0) {
+		// make.conf directories are loaded in order with variables expanded and files sourced
+		write_file(dir, "extra.conf", "EXTRA='from extra'\n");
+		write_file(dir, "make.conf/00-base",
+			"# comment\n"
+			"USE=\"a b\" # trailing comment\n"
+			"export ACCEPT_KEYWORDS=amd64\n"
+			"source ../extra.conf\n"
+			"FOO=${USE}\\\n"
+			"' c'\n"
+			"BAR=\"$FOO \\$x\"\n"
+			"LITERAL='$FOO'\n");
+		write_file(dir, "make.conf/01-override", "USE=\"${USE} -b d\"\n");
+		assert(pkgcraft_config_load_portage(config, dir));
+		check_var(config, "USE", "a b -b d");
+		check_var(config, "ACCEPT_KEYWORDS", "amd64");
+		check_var(config, "EXTRA", "from extra");
+		check_var(config, "FOO", "a b c");
+		check_var(config, "BAR", "a b c $x");
+		check_var(config, "LITERAL", "$FOO");
+		check_var(config, "UNSET", NULL);
+
+		// unsupported syntax is rejected
+		write_file(dir, "make.conf/02-invalid", "if true; then USE=a; fi\n");
+		assert(!pkgcraft_config_load_portage(config, dir));
+		assert(pkgcraft_last_error() != NULL);
+	} else {
+		char **(*func)(Config *, Pkg *, size_t *);
+		const char *name;
+		if (strcmp(kind, "use") == 0) {
+			func = pkgcraft_config_pkg_use;
+			name = "package.use";
+		} else if (strcmp(kind, "accept_keywords") == 0) {
+			func = pkgcraft_config_pkg_accept_keywords;
+			name = "package.accept_keywords";
+		} else if (strcmp(kind, "license") == 0) {
+			func = pkgcraft_config_pkg_license;
+			name = "package.license";
+		} else {
+			fprintf(stderr, "unknown portage config kind: %s\n", kind);
+			exit(1);
+		}
+
+		if (argc < 3) {
+			fprintf(stderr, "missing required cpv arg\n");
+			exit(1);
+		}
+		const char *cpv = argv[2];
+
+		// make.conf and package config file contents are passed via the environment
+		char *make_conf = getenv("make_conf");
+		if (make_conf) {
+			write_file(dir, "make.conf", make_conf);
+		}
+		char *entries = getenv("entries");
+		if (entries) {
+			write_file(dir, name, entries);
+		}
+
+		// invalid entries cause loading to fail
+		if (getenv("invalid")) {
+			assert(!pkgcraft_config_load_portage(config, dir));
+			assert(pkgcraft_last_error() != NULL);
+			pkgcraft_config_free(config);
+			remove_dir(dir);
+			free(dir);
+			return 0;
+		}
+		assert(pkgcraft_config_load_portage(config, dir));
+
+		r = pkgcraft_repo_fake_new("fake", 0, &cpv, 1);
+		assert(r != NULL);
+		iter = pkgcraft_repo_iter(r);
+		pkg = pkgcraft_repo_iter_next(iter);
+		assert(pkg != NULL);
+
+		values = func(config, pkg, &len);
+		assert(len == (size_t)(argc - 3));
+		for (size_t i = 0; i < len; i++) {
+			assert(strcmp(values[i], argv[i + 3]) == 0);
+		}
+
+		pkgcraft_str_array_free(values, len);
+		pkgcraft_pkg_free(pkg);
+		pkgcraft_repo_iter_free(iter);
+		pkgcraft_repo_free(r);
+	}
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
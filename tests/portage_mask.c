@@ -0,0 +1,70 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Write space-separated entries from an environment variable to a config file, one per line.
+static void write_entries(const char *dir, const char *name, const char *var) {
+	char *value = getenv(var);
+	if (value) {
+		char *data = strdup(value);
+		for (char *p = data; *p; p++) {
+			if (*p == ' ') {
+				*p = '\n';
+			}
+		}
+		write_file(dir, name, data);
+		free(data);
+	}
+}
+
+int main (int argc, char **argv) {
+	ConfigOptions options = {0};
+	Config *config;
+	Repo *r;
+	PkgIter *iter;
+	Pkg *pkg;
+	char path[4096];
+
+	if (argc != 3) {
+		fprintf(stderr, "usage: portage_mask cpv masked\n");
+		exit(1);
+	}
+
+	const char *cpv = argv[1];
+	bool expected = strcmp(argv[2], "1") == 0;
+	char *dir = temp_dir();
+
+	// entries from the second directory are loaded after those from the first
+	snprintf(path, sizeof(path), "%s/1", dir);
+	make_dirs(path);
+	write_entries(path, "package.mask", "mask");
+	write_entries(path, "package.unmask", "unmask");
+	snprintf(path, sizeof(path), "%s/2", dir);
+	make_dirs(path);
+	write_entries(path, "package.mask", "mask2");
+	write_entries(path, "package.unmask", "unmask2");
+
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+	snprintf(path, sizeof(path), "%s/1", dir);
+	assert(pkgcraft_config_load_portage(config, path));
+	snprintf(path, sizeof(path), "%s/2", dir);
+	assert(pkgcraft_config_load_portage(config, path));
+
+	r = pkgcraft_repo_fake_new("fake", 0, &cpv, 1);
+	assert(r != NULL);
+	iter = pkgcraft_repo_iter(r);
+	pkg = pkgcraft_repo_iter_next(iter);
+	assert(pkg != NULL);
+	assert(pkgcraft_config_pkg_masked(config, pkg) == expected);
+	pkgcraft_pkg_free(pkg);
+	pkgcraft_repo_iter_free(iter);
+	pkgcraft_repo_free(r);
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
@@ -0,0 +1,71 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	Repo *r;
+	PkgIter *iter;
+	RestrictPkgIter *restrict_iter;
+	Restrict *r_restrict;
+	Pkg *pkg;
+	size_t len, count;
+	char *restrict_str, *matches_str;
+	const char *cpvs = "cat/new-1";
+
+	if (argc < 2) {
+		fprintf(stderr, "missing required cpv args\n");
+		exit(1);
+	}
+
+	len = argc - 1;
+	r = pkgcraft_repo_fake_new("fake", 0, (const char **)&argv[1], len);
+	assert(r);
+	assert(pkgcraft_repo_len(r) == len);
+	assert(pkgcraft_repo_cmp(r, r) == 0);
+
+	// iterate over all packages
+	count = 0;
+	iter = pkgcraft_repo_iter(r);
+	while ((pkg = pkgcraft_repo_iter_next(iter))) {
+		count++;
+		pkgcraft_pkg_free(pkg);
+	}
+	pkgcraft_repo_iter_free(iter);
+	assert(count == len);
+
+	// iterate over packages matching a restriction
+	restrict_str = getenv("restrict");
+	matches_str = getenv("matches");
+	if (restrict_str && matches_str) {
+		count = 0;
+		r_restrict = pkgcraft_restrict_parse_dep(restrict_str);
+		restrict_iter = pkgcraft_repo_restrict_iter(r, r_restrict);
+		while ((pkg = pkgcraft_repo_restrict_iter_next(restrict_iter))) {
+			assert(pkgcraft_restrict_matches_pkg(r_restrict, pkg));
+			count++;
+			pkgcraft_pkg_free(pkg);
+		}
+		pkgcraft_repo_restrict_iter_free(restrict_iter);
+		pkgcraft_restrict_free(r_restrict);
+		assert(count == (size_t)atoi(matches_str));
+	}
+
+	// add and remove packages
+	assert(pkgcraft_repo_fake_add(r, &cpvs, 1));
+	assert(pkgcraft_repo_len(r) == len + 1);
+	assert(pkgcraft_repo_fake_remove(r, &cpvs, 1));
+	assert(pkgcraft_repo_len(r) == len);
+	assert(pkgcraft_repo_fake_remove(r, (const char **)&argv[1], len));
+	assert(pkgcraft_repo_len(r) == 0);
+
+	// invalid cpvs are rejected
+	cpvs = "cat/pkg";
+	assert(!pkgcraft_repo_fake_add(r, &cpvs, 1));
+	assert(!pkgcraft_repo_fake_new("fake", 0, &cpvs, 1));
+
+	pkgcraft_repo_free(r);
+
+	return 0;
+}
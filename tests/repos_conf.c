@@ -0,0 +1,197 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+// Load a repos.conf file or directory, returning the number of loaded repos.
+static size_t load(Config *config, const char *path) {
+	size_t len;
+	RepoConfig **repos = pkgcraft_config_load_repos_conf(config, path, &len);
+	assert(repos != NULL);
+	pkgcraft_repos_free(repos, len);
+	return len;
+}
+
+// Return the priority of a configured repo.
+static int priority(Config *config, const char *id) {
+	RepoConfig *r = pkgcraft_config_repo_get(config, id);
+	assert(r != NULL);
+	int priority = r->priority;
+	pkgcraft_repo_config_free(r);
+	return priority;
+}
+
+int main (int argc, char **argv) {
+	char *kind, *dir, *data, path[4096], conf[8192];
+	ConfigOptions options = {0};
+	Config *config;
+	size_t len;
+
+	if (argc == 2) {
+		kind = argv[1];
+	} else {
+		fprintf(stderr, "missing required repos.conf test kind arg\n");
+		exit(1);
+	}
+
+	dir = temp_dir();
+	snprintf(path, sizeof(path), "%s/a", dir);
+	ebuild_repo(path, "a");
+	snprintf(path, sizeof(path), "%s/b", dir);
+	ebuild_repo(path, "b");
+	snprintf(path, sizeof(path), "%s/etc", dir);
+	options.prefix = dir;
+	options.config_dir = path;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+
+	if (strcmp(kind, "save_file") == 0) {
+		snprintf(conf, sizeof(conf), "[a]\nlocation = %s/a\npriority = 1\n", dir);
+		write_file(dir, "etc/repos.conf", conf);
+		snprintf(path, sizeof(path), "%s/etc/repos.conf", dir);
+		assert(load(config, path) == 1);
+
+		// comments, unknown settings, and sections for other repos are preserved
+		snprintf(conf, sizeof(conf),
+			"# repos\n"
+			"[DEFAULT]\n"
+			"main-repo = a\n"
+			"\n"
+			"[a]\n"
+			"location = %s/a\n"
+			"# priority comment\n"
+			"priority = 1\n"
+			"auto-sync = yes\n"
+			"\n"
+			"[other]\n"
+			"location = /nonexistent\n", dir);
+		write_file(dir, "etc/repos.conf", conf);
+		assert(pkgcraft_config_repo_set_priority(config, "a", 2));
+		assert(pkgcraft_config_save_repos_conf(config, NULL));
+		data = read_file(dir, "etc/repos.conf");
+		snprintf(conf, sizeof(conf),
+			"# repos\n"
+			"[DEFAULT]\n"
+			"main-repo = a\n"
+			"\n"
+			"[a]\n"
+			"location = %s/a\n"
+			"# priority comment\n"
+			"priority = 2\n"
+			"auto-sync = yes\n"
+			"\n"
+			"[other]\n"
+			"location = /nonexistent\n", dir);
+		assert(strcmp(data, conf) == 0);
+		free(data);
+	} else if (strcmp(kind, "save_dir") == 0) {
+		// files are never removed, even when their repos are no longer registered
+		snprintf(conf, sizeof(conf), "[a]\nlocation = %s/a\npriority = 1\n", dir);
+		write_file(dir, "etc/repos.conf/a.conf", conf);
+		snprintf(path, sizeof(path), "%s/etc/repos.conf", dir);
+		assert(load(config, path) == 1);
+		write_file(dir, "etc/repos.conf/other.conf", "[other]\nlocation = /nonexistent\n");
+		assert(pkgcraft_config_repo_remove(config, "a"));
+		snprintf(path, sizeof(path), "%s/b", dir);
+		RepoConfig *r = pkgcraft_config_add_repo_path(config, "b", 3, path);
+		assert(r != NULL);
+		pkgcraft_repo_config_free(r);
+		assert(pkgcraft_config_save_repos_conf(config, NULL));
+
+		data = read_file(dir, "etc/repos.conf/a.conf");
+		assert(strcmp(data, conf) == 0);
+		free(data);
+		data = read_file(dir, "etc/repos.conf/other.conf");
+		assert(strcmp(data, "[other]\nlocation = /nonexistent\n") == 0);
+		free(data);
+		// new repos are written to separate files
+		data = read_file(dir, "etc/repos.conf/b.conf");
+		snprintf(conf, sizeof(conf), "[b]\nlocation = %s/b\npriority = 3\n", dir);
+		assert(strcmp(data, conf) == 0);
+		free(data);
+	} else if (strcmp(kind, "last_wins") == 0) {
+		// later sections override earlier ones, matching portage
+		snprintf(conf, sizeof(conf), "[a]\nlocation = %s/a\npriority = 1\n", dir);
+		write_file(dir, "etc/repos.conf/00.conf", conf);
+		write_file(dir, "etc/repos.conf/10.conf", "[a]\npriority = 5\n");
+		snprintf(path, sizeof(path), "%s/etc/repos.conf", dir);
+		assert(load(config, path) == 1);
+		assert(priority(config, "a") == 5);
+
+		// saving updates the overriding section
+		assert(pkgcraft_config_repo_set_priority(config, "a", 7));
+		assert(pkgcraft_config_save_repos_conf(config, NULL));
+		data = read_file(dir, "etc/repos.conf/00.conf");
+		assert(strcmp(data, conf) == 0);
+		free(data);
+		data = read_file(dir, "etc/repos.conf/10.conf");
+		snprintf(conf, sizeof(conf), "[a]\npriority = 7\nlocation = %s/a\n", dir);
+		assert(strcmp(data, conf) == 0);
+		free(data);
+	} else if (strcmp(kind, "no_location") == 0) {
+		// repos without a location aren't saved
+		write_file(dir, "etc/portage/repos.conf",
+			"[remote]\nsync-type = git\nsync-uri = https://a.com/remote.git\n");
+		snprintf(path, sizeof(path), "%s/etc/portage/repos.conf", dir);
+		assert(load(config, path) == 1);
+		assert(pkgcraft_config_save_repos_conf(config, NULL));
+		assert(read_file(dir, "etc/repos.conf") == NULL);
+	} else if (strcmp(kind, "fields") == 0) {
+		// all configured settings are exposed via repo configs
+		snprintf(conf, sizeof(conf),
+			"[a]\n"
+			"location = %s/a\n"
+			"[b]\n"
+			"location = %s/b\n"
+			"priority = 3\n"
+			"sync-type = git\n"
+			"sync-uri = https://a.com/b.git\n"
+			"masters = a\n", dir, dir);
+		write_file(dir, "etc/repos.conf", conf);
+		snprintf(path, sizeof(path), "%s/etc/repos.conf", dir);
+		assert(load(config, path) == 2);
+
+		RepoConfig *r = pkgcraft_config_repo_get(config, "b");
+		assert(r != NULL);
+		assert(strcmp(r->id, "b") == 0);
+		assert(r->format == Ebuild);
+		snprintf(path, sizeof(path), "%s/b", dir);
+		assert(strcmp(r->location, path) == 0);
+		assert(r->priority == 3);
+		assert(strcmp(r->sync_type, "git") == 0);
+		assert(strcmp(r->sync_uri, "https://a.com/b.git") == 0);
+		assert(r->masters_len == 1);
+		assert(strcmp(r->masters[0], "a") == 0);
+		pkgcraft_repo_config_free(r);
+
+		// unset settings are NULL
+		r = pkgcraft_config_repo_get(config, "a");
+		assert(r != NULL);
+		snprintf(path, sizeof(path), "%s/a", dir);
+		assert(strcmp(r->location, path) == 0);
+		assert(r->priority == 0);
+		assert(r->sync_type == NULL);
+		assert(r->sync_uri == NULL);
+		assert(r->masters == NULL);
+		assert(r->masters_len == 0);
+		pkgcraft_repo_config_free(r);
+	} else if (strcmp(kind, "no_autoload") == 0) {
+		// configs don't implicitly load the repos.conf file in their config directory
+		pkgcraft_config_free(config);
+		snprintf(conf, sizeof(conf), "[a]\nlocation = %s/a\n", dir);
+		write_file(dir, "etc/repos.conf", conf);
+		config = pkgcraft_config_new(&options);
+		assert(config != NULL);
+		RepoConfig **repos = pkgcraft_config_repos(config, &len);
+		assert(len == 0);
+		pkgcraft_repos_free(repos, len);
+	} else {
+		fprintf(stderr, "unknown repos.conf test kind: %s\n", kind);
+		exit(1);
+	}
+
+	pkgcraft_config_free(config);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
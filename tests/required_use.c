@@ -0,0 +1,43 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *required_use, *flags_env, *flag;
+	char *flags[16];
+	size_t flags_len = 0, length;
+	DepSet *d = NULL;
+	RequiredUseFailure **failures = NULL;
+
+	if (argc == 2) {
+		required_use = argv[1];
+	} else if (argc < 2) {
+		fprintf(stderr, "missing required REQUIRED_USE arg\n");
+		exit(1);
+	}
+
+	flags_env = getenv("flags");
+	if (flags_env) {
+		flag = strtok(flags_env, ",");
+		while (flag && flags_len < 16) {
+			flags[flags_len++] = flag;
+			flag = strtok(NULL, ",");
+		}
+	}
+
+	d = pkgcraft_depset_required_use(required_use, NULL);
+	assert(d != NULL);
+
+	failures = pkgcraft_required_use_failures(d, (const char **)flags, flags_len, &length);
+	assert(failures != NULL);
+	assert(length == (size_t)atoi(getenv("failures")));
+	assert(pkgcraft_required_use_satisfied(d, (const char **)flags, flags_len) == (length == 0));
+
+	pkgcraft_required_use_failures_free(failures, length);
+	pkgcraft_depset_free(d);
+
+	return 0;
+}
@@ -0,0 +1,42 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *restrict_str, *atom_str;
+	Restrict *r, *not_r;
+	Atom *a;
+	bool expected;
+
+	if (argc == 4) {
+		restrict_str = argv[1];
+		atom_str = argv[2];
+		expected = atoi(argv[3]);
+	} else if (argc < 4) {
+		fprintf(stderr, "missing required restriction args\n");
+		exit(1);
+	}
+
+	r = pkgcraft_restrict_parse_dep(restrict_str);
+	a = pkgcraft_atom(atom_str, NULL);
+	assert(pkgcraft_restrict_matches_atom(r, a) == expected);
+
+	// inverted restrictions match the opposite
+	not_r = pkgcraft_restrict_not(r);
+	assert(pkgcraft_restrict_matches_atom(not_r, a) != expected);
+
+	// the cpv string of a versioned atom matches the same as the atom
+	if (pkgcraft_atom_version(a)) {
+		char *cpv = pkgcraft_atom_cpv(a);
+		assert(pkgcraft_restrict_matches_cpv_str(r, cpv) == expected);
+		pkgcraft_str_free(cpv);
+	}
+
+	pkgcraft_restrict_free(r);
+	pkgcraft_restrict_free(not_r);
+	pkgcraft_atom_free(a);
+
+	return 0;
+}
@@ -0,0 +1,78 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+// match packages with a given name, counting the number of calls
+struct data {
+	const char *package;
+	size_t calls;
+};
+
+bool package_matches(const Pkg *p, void *data) {
+	struct data *d = data;
+	char *package = pkgcraft_atom_package((Atom *)pkgcraft_pkg_atom((Pkg *)p));
+	bool matches = strcmp(package, d->package) == 0;
+	pkgcraft_str_free(package);
+	d->calls++;
+	return matches;
+}
+
+int main (int argc, char **argv) {
+	struct data d = {0};
+	char atom_str[256];
+	Restrict *r, *not_r;
+	RestrictPkgIter *iter;
+	Repo *repo;
+	Pkg *pkg;
+	Atom *a;
+	size_t len, count = 0;
+
+	if (argc < 3) {
+		fprintf(stderr, "missing required package name and cpv args\n");
+		exit(1);
+	}
+
+	d.package = argv[1];
+	len = argc - 2;
+	repo = pkgcraft_repo_fake_new("fake", 0, (const char **)&argv[2], len);
+	assert(repo != NULL);
+
+	// NULL callbacks are rejected
+	assert(pkgcraft_restrict_pkg_callback(NULL, NULL) == NULL);
+	assert(pkgcraft_last_error() != NULL);
+
+	r = pkgcraft_restrict_pkg_callback(package_matches, &d);
+	assert(r != NULL);
+	assert(pkgcraft_restrict_kind(r) == Callback);
+	not_r = pkgcraft_restrict_not(r);
+
+	// callbacks are run for each package
+	iter = pkgcraft_repo_restrict_iter(repo, r);
+	while ((pkg = pkgcraft_repo_restrict_iter_next(iter))) {
+		assert(!pkgcraft_restrict_matches_pkg(not_r, pkg));
+		count++;
+		pkgcraft_pkg_free(pkg);
+	}
+	pkgcraft_repo_restrict_iter_free(iter);
+	assert(count == (size_t)atoi(getenv("matches")));
+	assert(d.calls >= len);
+
+	// callbacks never match atoms, even when inverted
+	snprintf(atom_str, sizeof(atom_str), "=%s", argv[2]);
+	a = pkgcraft_atom(atom_str, NULL);
+	assert(a != NULL);
+	d.calls = 0;
+	assert(!pkgcraft_restrict_matches_atom(r, a));
+	assert(!pkgcraft_restrict_matches_atom(not_r, a));
+	assert(d.calls == 0);
+
+	pkgcraft_atom_free(a);
+	pkgcraft_restrict_free(not_r);
+	pkgcraft_restrict_free(r);
+	pkgcraft_repo_free(repo);
+
+	return 0;
+}
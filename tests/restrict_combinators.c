@@ -0,0 +1,116 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *op, *atom_str, *value, *expected_str, *cpvs_env, *cpv, *glob;
+	char *cpvs[16];
+	Restrict *restricts[16], **children, *r;
+	RestrictKind expected_kind;
+	RestrictPkgIter *iter;
+	Repo *repo;
+	Pkg *pkg;
+	Atom *a;
+	bool expected;
+	size_t len = 0, cpvs_len = 0, children_len, count;
+
+	if (argc >= 4) {
+		op = argv[1];
+		atom_str = argv[2];
+		expected = atoi(argv[3]);
+	} else {
+		fprintf(stderr, "missing required restriction args\n");
+		exit(1);
+	}
+
+	// remaining args are dependency restrictions to combine
+	for (int i = 4; i < argc && len < 16; i++) {
+		restricts[len] = pkgcraft_restrict_parse_dep(argv[i]);
+		assert(restricts[len] != NULL);
+		len++;
+	}
+
+	// optionally combine with a non-native glob restriction
+	glob = getenv("glob");
+	if (glob) {
+		restricts[len] = pkgcraft_restrict_glob(glob);
+		assert(restricts[len] != NULL);
+		len++;
+	}
+
+	if (strcmp(op, "true") == 0) {
+		r = pkgcraft_restrict_true();
+		expected_kind = True;
+	} else if (strcmp(op, "false") == 0) {
+		r = pkgcraft_restrict_false();
+		expected_kind = False;
+	} else if (strcmp(op, "and") == 0) {
+		r = pkgcraft_restrict_and(restricts, len);
+		expected_kind = And;
+	} else if (strcmp(op, "or") == 0) {
+		r = pkgcraft_restrict_or(restricts, len);
+		expected_kind = Or;
+	} else if (strcmp(op, "not") == 0) {
+		assert(len == 1);
+		r = pkgcraft_restrict_not(restricts[0]);
+		expected_kind = Not;
+	} else {
+		fprintf(stderr, "unknown restriction combinator: %s\n", op);
+		exit(1);
+	}
+	assert(r != NULL);
+
+	a = pkgcraft_atom(atom_str, NULL);
+	assert(a != NULL);
+	assert(pkgcraft_restrict_matches_atom(r, a) == expected);
+
+	// combinations can be walked back to the restrictions they were created from
+	assert(pkgcraft_restrict_kind(r) == expected_kind);
+	children = pkgcraft_restrict_children(r, &children_len);
+	assert(children_len == len);
+	for (size_t i = 0; i < children_len; i++) {
+		assert(pkgcraft_restrict_eq(children[i], restricts[i]));
+	}
+	pkgcraft_restricts_free(children, children_len);
+
+	expected_str = getenv("str");
+	if (expected_str) {
+		value = pkgcraft_restrict_str(r);
+		assert(strcmp(value, expected_str) == 0);
+		pkgcraft_str_free(value);
+	}
+
+	// iterate over the matching packages of a repo
+	cpvs_env = getenv("cpvs");
+	if (cpvs_env) {
+		cpv = strtok(cpvs_env, ",");
+		while (cpv && cpvs_len < 16) {
+			cpvs[cpvs_len++] = cpv;
+			cpv = strtok(NULL, ",");
+		}
+
+		repo = pkgcraft_repo_fake_new("fake", 0, (const char **)cpvs, cpvs_len);
+		assert(repo != NULL);
+		count = 0;
+		iter = pkgcraft_repo_restrict_iter(repo, r);
+		while ((pkg = pkgcraft_repo_restrict_iter_next(iter))) {
+			assert(pkgcraft_restrict_matches_pkg(r, pkg));
+			count++;
+			pkgcraft_pkg_free(pkg);
+		}
+		assert(count == (size_t)atoi(getenv("matches")));
+		pkgcraft_repo_restrict_iter_free(iter);
+		pkgcraft_repo_free(repo);
+	}
+
+	for (size_t i = 0; i < len; i++) {
+		pkgcraft_restrict_free(restricts[i]);
+	}
+	pkgcraft_restrict_free(r);
+	pkgcraft_atom_free(a);
+
+	return 0;
+}
@@ -0,0 +1,78 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *kind, *pattern, *atom_str, *value;
+	Restrict *r, *not_r;
+	RestrictKind expected_kind;
+	Atom *a;
+	bool expected;
+
+	if (argc == 5) {
+		kind = argv[1];
+		pattern = argv[2];
+		atom_str = argv[3];
+		expected = atoi(argv[4]);
+	} else {
+		fprintf(stderr, "missing required restriction args\n");
+		exit(1);
+	}
+
+	if (strcmp(kind, "glob") == 0) {
+		r = pkgcraft_restrict_glob(pattern);
+		expected_kind = And;
+	} else if (strcmp(kind, "category_glob") == 0) {
+		r = pkgcraft_restrict_category_glob(pattern);
+		expected_kind = Glob;
+	} else if (strcmp(kind, "package_glob") == 0) {
+		r = pkgcraft_restrict_package_glob(pattern);
+		expected_kind = Glob;
+	} else if (strcmp(kind, "category_regex") == 0) {
+		r = pkgcraft_restrict_category_regex(pattern);
+		expected_kind = Regex;
+	} else if (strcmp(kind, "package_regex") == 0) {
+		r = pkgcraft_restrict_package_regex(pattern);
+		expected_kind = Regex;
+	} else if (strcmp(kind, "description_regex") == 0) {
+		r = pkgcraft_restrict_description_regex(pattern);
+		expected_kind = Regex;
+	} else {
+		fprintf(stderr, "unknown restriction kind: %s\n", kind);
+		exit(1);
+	}
+
+	// invalid patterns are rejected
+	if (getenv("invalid")) {
+		assert(r == NULL);
+		assert(pkgcraft_last_error() != NULL);
+		return 0;
+	}
+
+	assert(r != NULL);
+	assert(pkgcraft_restrict_kind(r) == expected_kind);
+	value = pkgcraft_restrict_str(r);
+	assert(strcmp(value, getenv("str")) == 0);
+	pkgcraft_str_free(value);
+
+	a = pkgcraft_atom(atom_str, NULL);
+	assert(a != NULL);
+	assert(pkgcraft_restrict_matches_atom(r, a) == expected);
+
+	// inverted restrictions match the opposite unless they don't apply to atoms
+	not_r = pkgcraft_restrict_not(r);
+	if (getenv("inapplicable")) {
+		assert(!pkgcraft_restrict_matches_atom(not_r, a));
+	} else {
+		assert(pkgcraft_restrict_matches_atom(not_r, a) != expected);
+	}
+
+	pkgcraft_restrict_free(not_r);
+	pkgcraft_restrict_free(r);
+	pkgcraft_atom_free(a);
+
+	return 0;
+}
@@ -0,0 +1,90 @@
+#include "utils.h"
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *query, *matches_str, *dir, path[4096];
+	ConfigOptions options = {0};
+	Config *config;
+	RepoConfig *repo_conf;
+	RestrictPkgIter *iter;
+	Restrict *r;
+	Pkg *pkg;
+	size_t count = 0;
+
+	if (argc == 2) {
+		query = argv[1];
+	} else {
+		fprintf(stderr, "missing required package query arg\n");
+		exit(1);
+	}
+
+	// invalid queries fail to parse
+	r = pkgcraft_restrict_parse_pkg(query);
+	matches_str = getenv("matches");
+	if (!matches_str) {
+		assert(r == NULL);
+		assert(pkgcraft_last_error() != NULL);
+		return 0;
+	}
+	assert(r != NULL);
+
+	dir = temp_dir();
+	snprintf(path, sizeof(path), "%s/repo", dir);
+	ebuild_repo(path, "test");
+	write_file(path, "dev-lang/rust/rust-1.ebuild",
+		"EAPI=8\n"
+		"DESCRIPTION=\"rust compiler\"\n"
+		"HOMEPAGE=\"https://www.rust-lang.org/\"\n"
+		"SLOT=\"0\"\n"
+		"LICENSE=\"MIT Apache-2.0\"\n"
+		"KEYWORDS=\"amd64 ~arm64\"\n"
+		"IUSE=\"doc test\"\n");
+	write_file(path, "dev-lang/rust/rust-2.ebuild",
+		"EAPI=7\n"
+		"DESCRIPTION=\"rust compiler\"\n"
+		"HOMEPAGE=\"https://www.rust-lang.org/\"\n"
+		"SLOT=\"2\"\n"
+		"LICENSE=\"MIT Apache-2.0\"\n"
+		"KEYWORDS=\"~amd64\"\n"
+		"IUSE=\"doc\"\n");
+	write_file(path, "dev-lang/rust/metadata.xml",
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"
+		"<pkgmetadata>\n"
+		"\t<maintainer type=\"project\">\n"
+		"\t\t<email>rust@gentoo.org</email>\n"
+		"\t\t<name>Rust</name>\n"
+		"\t</maintainer>\n"
+		"</pkgmetadata>\n");
+	write_file(path, "app-misc/foo/foo-1.ebuild",
+		"EAPI=7\n"
+		"DESCRIPTION=\"foo utility\"\n"
+		"HOMEPAGE=\"https://github.com/foo/foo\"\n"
+		"SLOT=\"0\"\n"
+		"LICENSE=\"GPL-2\"\n"
+		"KEYWORDS=\"~amd64\"\n"
+		"IUSE=\"+bar\"\n");
+
+	options.prefix = dir;
+	config = pkgcraft_config_new(&options);
+	assert(config != NULL);
+	repo_conf = pkgcraft_config_add_repo_path(config, "test", 0, path);
+	assert(repo_conf != NULL);
+
+	iter = pkgcraft_repo_restrict_iter(repo_conf->repo, r);
+	while ((pkg = pkgcraft_repo_restrict_iter_next(iter))) {
+		assert(pkgcraft_restrict_matches_pkg(r, pkg));
+		count++;
+		pkgcraft_pkg_free(pkg);
+	}
+	assert(count == (size_t)atoi(matches_str));
+
+	pkgcraft_repo_restrict_iter_free(iter);
+	pkgcraft_repo_config_free(repo_conf);
+	pkgcraft_config_free(config);
+	pkgcraft_restrict_free(r);
+	remove_dir(dir);
+	free(dir);
+
+	return 0;
+}
@@ -0,0 +1,76 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *value, *kind_str;
+	Restrict *r, *dup, *combined, *simplified, **children;
+	Restrict *restricts[2];
+	RestrictKind kind;
+	size_t len;
+
+	if (argc != 2) {
+		fprintf(stderr, "missing required restriction arg\n");
+		exit(1);
+	}
+
+	r = pkgcraft_restrict_parse_dep(argv[1]);
+	assert(r != NULL);
+	if (getenv("not")) {
+		Restrict *not_r = pkgcraft_restrict_not(r);
+		pkgcraft_restrict_free(r);
+		r = not_r;
+	}
+
+	// native restrictions are rendered readably
+	value = pkgcraft_restrict_str(r);
+	assert(strcmp(value, getenv("str")) == 0);
+	pkgcraft_str_free(value);
+
+	// native restrictions can be walked
+	kind_str = getenv("kind");
+	if (strcmp(kind_str, "and") == 0) {
+		kind = And;
+	} else if (strcmp(kind_str, "not") == 0) {
+		kind = Not;
+	} else {
+		kind = Native;
+	}
+	assert(pkgcraft_restrict_kind(r) == kind);
+	children = pkgcraft_restrict_children(r, &len);
+	assert(len == (size_t)atoi(getenv("children")));
+	for (size_t i = 0; i < len; i++) {
+		assert(pkgcraft_restrict_kind(children[i]) != True);
+		value = pkgcraft_restrict_str(children[i]);
+		assert(strlen(value) > 0);
+		pkgcraft_str_free(value);
+	}
+	pkgcraft_restricts_free(children, len);
+
+	// equal restrictions hash the same
+	dup = pkgcraft_restrict_parse_dep(argv[1]);
+	if (getenv("not")) {
+		Restrict *not_dup = pkgcraft_restrict_not(dup);
+		pkgcraft_restrict_free(dup);
+		dup = not_dup;
+	}
+	assert(pkgcraft_restrict_eq(r, dup));
+	assert(pkgcraft_restrict_hash(r) == pkgcraft_restrict_hash(dup));
+
+	// duplicate terms are removed when simplifying
+	restricts[0] = r;
+	restricts[1] = dup;
+	combined = pkgcraft_restrict_and(restricts, 2);
+	simplified = pkgcraft_restrict_simplify(combined);
+	assert(pkgcraft_restrict_eq(simplified, r));
+
+	pkgcraft_restrict_free(simplified);
+	pkgcraft_restrict_free(combined);
+	pkgcraft_restrict_free(dup);
+	pkgcraft_restrict_free(r);
+
+	return 0;
+}
@@ -0,0 +1,61 @@
+#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include <pkgcraft.h>
+
+int main (int argc, char **argv) {
+	char *src_uri, *restrict_env, *flags_env, *flag, *expected;
+	char *flags[16];
+	size_t flags_len = 0, length;
+	DepSet *d = NULL, *r = NULL;
+	SrcUri **uris = NULL;
+
+	if (argc == 2) {
+		src_uri = argv[1];
+	} else if (argc < 2) {
+		fprintf(stderr, "missing required SRC_URI arg\n");
+		exit(1);
+	}
+
+	flags_env = getenv("flags");
+	if (flags_env) {
+		flag = strtok(flags_env, ",");
+		while (flag && flags_len < 16) {
+			flags[flags_len++] = flag;
+			flag = strtok(NULL, ",");
+		}
+	}
+
+	d = pkgcraft_depset_src_uri(src_uri, NULL);
+	assert(d != NULL);
+
+	restrict_env = getenv("restrict");
+	if (restrict_env) {
+		r = pkgcraft_depset_restrict(restrict_env);
+		assert(r != NULL);
+	}
+
+	uris = pkgcraft_src_uris(d, r, (const char **)flags, flags_len, &length);
+	assert(uris != NULL);
+	assert(length == (size_t)atoi(getenv("uris")));
+
+	// verify the fields of the first entry
+	assert(strcmp(uris[0]->uri, getenv("uri")) == 0);
+	assert(strcmp(uris[0]->filename, getenv("filename")) == 0);
+	expected = getenv("mirror");
+	if (expected) {
+		assert(strcmp(uris[0]->mirror, expected) == 0);
+	} else {
+		assert(uris[0]->mirror == NULL);
+	}
+	assert(uris[0]->fetch_restricted == (getenv("fetch_restricted") != NULL));
+	assert(uris[0]->mirror_restricted == (getenv("mirror_restricted") != NULL));
+
+	pkgcraft_src_uris_free(uris, length);
+	pkgcraft_depset_free(r);
+	pkgcraft_depset_free(d);
+
+	return 0;
+}
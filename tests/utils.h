@@ -0,0 +1,79 @@
+// Helpers for tests requiring files on disk, included before any other headers.
+
+#define _XOPEN_SOURCE 700
+
+#include <assert.h>
+#include <ftw.h>
+#include <libgen.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/stat.h>
+
+// Create a directory and all its missing parents.
+static void make_dirs(const char *path) {
+	char *dir = strdup(path);
+	char *parent = strdup(path);
+	if (strcmp(dir, "/") != 0 && strcmp(dir, ".") != 0) {
+		make_dirs(dirname(parent));
+		mkdir(dir, 0755);
+	}
+	free(parent);
+	free(dir);
+}
+
+// Write a file relative to a given directory, creating any missing parent directories.
+static void write_file(const char *dir, const char *name, const char *data) {
+	char path[4096], parent[4096];
+	snprintf(path, sizeof(path), "%s/%s", dir, name);
+	strcpy(parent, path);
+	make_dirs(dirname(parent));
+	FILE *f = fopen(path, "w");
+	assert(f != NULL);
+	fputs(data, f);
+	fclose(f);
+}
+
+// Read a file relative to a given directory, returning NULL if it doesn't exist.
+static char *read_file(const char *dir, const char *name) {
+	char path[4096];
+	snprintf(path, sizeof(path), "%s/%s", dir, name);
+	FILE *f = fopen(path, "r");
+	if (!f) {
+		return NULL;
+	}
+	fseek(f, 0, SEEK_END);
+	long len = ftell(f);
+	rewind(f);
+	char *data = calloc(len + 1, 1);
+	assert(fread(data, 1, len, f) == (size_t)len);
+	fclose(f);
+	return data;
+}
+
+static int remove_path(const char *path, const struct stat *sb, int flag, struct FTW *ftw) {
+	return remove(path);
+}
+
+// Recursively remove a directory.
+static void remove_dir(const char *path) {
+	nftw(path, remove_path, 16, FTW_DEPTH | FTW_PHYS);
+}
+
+// Create a temporary directory, returning its path.
+static char *temp_dir(void) {
+	char *tmpdir = getenv("TMPDIR");
+	char template[4096];
+	snprintf(template, sizeof(template), "%s/pkgcraft-XXXXXX", tmpdir ? tmpdir : "/tmp");
+	char *path = mkdtemp(template);
+	assert(path != NULL);
+	return strdup(path);
+}
+
+// Create an ebuild repo with a given id at a path.
+static void ebuild_repo(const char *path, const char *id) {
+	char repo_name[256];
+	snprintf(repo_name, sizeof(repo_name), "%s\n", id);
+	write_file(path, "profiles/repo_name", repo_name);
+	write_file(path, "metadata/layout.conf", "masters =\n");
+}